@@ -0,0 +1,42 @@
+use crate::{
+    agents::{Agent, is_safe, manhattan_distance, possible_moves},
+    game::{Direction, world::World},
+};
+
+// Straight for the apple, with avoid_collisions only over cells that don't kill the snake
+pub struct GreedyAgent {
+    pub avoid_collisions: bool,
+}
+impl Agent for GreedyAgent {
+    fn name(&self) -> &'static str {
+        if self.avoid_collisions {
+            return "safe-greedy";
+        }
+        return "greedy";
+    }
+    fn choose(&mut self, world: &World) -> Direction {
+        return greedy_move(world, self.avoid_collisions);
+    }
+}
+
+// Closest move to the apple, a deadly one only when nothing else is left
+pub fn greedy_move(world: &World, avoid_collisions: bool) -> Direction {
+    let head = world.snake.points[0];
+    let moves = possible_moves(world);
+    let safe_moves: Vec<Direction> = moves
+        .iter()
+        .copied()
+        .filter(|direction| is_safe(world, head + direction.to_point()))
+        .collect();
+    let candidates = if avoid_collisions && !safe_moves.is_empty() {
+        safe_moves
+    } else {
+        moves
+    };
+    return candidates
+        .into_iter()
+        .min_by_key(|direction| {
+            manhattan_distance(head + direction.to_point(), world.apple.position)
+        })
+        .unwrap();
+}
@@ -0,0 +1,120 @@
+use crate::{
+    agents::{Agent, greedy::greedy_move, is_safe, possible_moves},
+    game::{Direction, Point, world::World},
+};
+
+/*
+ * Walks a cycle through every free cell, so the body always lies along the cycle behind the
+ * head and the snake can't die before it fills the board. It skips parts of the cycle that the
+ * tail has already left, as long as it doesn't pass the apple and the body keeps spanning at most
+ * half of the cycle, so the skipped cells are left behind long before the snake needs them.
+ * Boards with obstacles or without a cycle, two odd sides, fall back to the safe greedy moves.
+ */
+#[derive(Default)]
+pub struct HamiltonianAgent {
+    // Position of every cell on the cycle, None for cells it doesn't go through
+    order: Vec<Option<usize>>,
+    cycle_length: usize,
+    width: i32,
+}
+impl Agent for HamiltonianAgent {
+    fn name(&self) -> &'static str {
+        return "hamiltonian";
+    }
+    fn reset(&mut self, world: &World) {
+        self.width = world.width;
+        self.cycle_length = world.free_cells();
+        self.order = if world.obstacles.is_empty() {
+            build_cycle(world.width, world.height)
+        } else {
+            vec![]
+        };
+    }
+    fn choose(&mut self, world: &World) -> Direction {
+        if self.order.is_empty() {
+            return greedy_move(world, true);
+        }
+        let points = &world.snake.points;
+        let head = points[0];
+        // Cells ahead of the head on the cycle, the tail counts as the whole cycle away at length 1
+        let ahead = |point: Point| {
+            let position = self.order[(point.y * self.width + point.x) as usize].unwrap_or(0);
+            let head_position = self.order[(head.y * self.width + head.x) as usize].unwrap_or(0);
+            return (position + self.cycle_length - head_position) % self.cycle_length;
+        };
+        let to_tail = match points.len() {
+            1 => self.cycle_length,
+            _ => ahead(points[points.len() - 1]),
+        };
+        let to_apple = match ahead(world.apple.position) {
+            0 => self.cycle_length,
+            distance => distance,
+        };
+        let mut best: Option<(Direction, usize)> = None;
+        for direction in possible_moves(world) {
+            let next = head + direction.to_point();
+            if !is_safe(world, next) {
+                continue;
+            }
+            let distance = ahead(next);
+            let on_cycle = distance == 1;
+            let shortcut = distance > 1
+                && distance <= to_apple
+                && distance < to_tail
+                && (to_tail - distance) * 2 >= self.cycle_length;
+            if (on_cycle || shortcut) && best.is_none_or(|(_, best)| distance > best) {
+                best = Some((direction, distance));
+            }
+        }
+        return match best {
+            Some((direction, _)) => direction,
+            None => greedy_move(world, true),
+        };
+    }
+}
+
+/*
+ * Along the top row of the inside, then up and down every column but the first, back up the
+ * first column. That needs an even number of columns, otherwise the board is walked transposed.
+ */
+fn build_cycle(width: i32, height: i32) -> Vec<Option<usize>> {
+    let (columns, rows) = (width - 2, height - 2);
+    let transposed = columns % 2 != 0;
+    let (columns, rows) = if transposed {
+        (rows, columns)
+    } else {
+        (columns, rows)
+    };
+    if columns % 2 != 0 || columns < 2 || rows < 2 {
+        return vec![];
+    }
+    let mut cells = vec![];
+    for column in 0..columns {
+        cells.push((column, 0));
+    }
+    for column in (1..columns).rev() {
+        let going_down = (columns - 1 - column) % 2 == 0;
+        for row_id in 1..rows {
+            let row = if going_down { row_id } else { rows - row_id };
+            cells.push((column, row));
+        }
+    }
+    for row in (1..rows).rev() {
+        cells.push((0, row));
+    }
+    let mut order = vec![None; (width * height) as usize];
+    for (position, (column, row)) in cells.into_iter().enumerate() {
+        let point = match transposed {
+            true => Point {
+                x: row + 1,
+                y: column + 1,
+            },
+            false => Point {
+                x: column + 1,
+                y: row + 1,
+            },
+        };
+        order[(point.y * width + point.x) as usize] = Some(position);
+    }
+    return order;
+}
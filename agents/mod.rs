@@ -0,0 +1,118 @@
+use crate::{
+    game::{Direction, Point, evaluation::evaluation_world, world::World},
+    perceptron::network::Network,
+};
+
+pub mod greedy;
+pub mod hamiltonian;
+pub mod network;
+pub mod pathfinding;
+pub mod random;
+
+pub const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+// Controller that plays a world by the same rules as the networks, scripted or a network itself
+pub trait Agent: Send {
+    fn name(&self) -> &'static str;
+    // Called once before every episode, the world is in its initial state
+    fn reset(&mut self, _world: &World) {}
+    fn choose(&mut self, world: &World) -> Direction;
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AgentKind {
+    Random,
+    Greedy,
+    SafeGreedy,
+    Pathfinding,
+    Hamiltonian,
+}
+impl AgentKind {
+    pub const ALL: [AgentKind; 5] = [
+        AgentKind::Random,
+        AgentKind::Greedy,
+        AgentKind::SafeGreedy,
+        AgentKind::Pathfinding,
+        AgentKind::Hamiltonian,
+    ];
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "random" => Some(AgentKind::Random),
+            "greedy" => Some(AgentKind::Greedy),
+            "safe-greedy" => Some(AgentKind::SafeGreedy),
+            "pathfinding" | "bfs" => Some(AgentKind::Pathfinding),
+            "hamiltonian" => Some(AgentKind::Hamiltonian),
+            _ => None,
+        };
+    }
+    pub fn create(&self) -> Box<dyn Agent> {
+        return match self {
+            AgentKind::Random => Box::new(random::RandomAgent::default()),
+            AgentKind::Greedy => Box::new(greedy::GreedyAgent {
+                avoid_collisions: false,
+            }),
+            AgentKind::SafeGreedy => Box::new(greedy::GreedyAgent {
+                avoid_collisions: true,
+            }),
+            AgentKind::Pathfinding => Box::new(pathfinding::PathfindingAgent),
+            AgentKind::Hamiltonian => Box::new(hamiltonian::HamiltonianAgent::default()),
+        };
+    }
+}
+
+// Plays the world until the snake dies or wins, fitness is calculated like for the networks
+pub fn play_agent(agent: &mut dyn Agent, world: &mut World) {
+    agent.reset(world);
+    while world.alive {
+        let direction = agent.choose(world);
+        world.step(direction);
+    }
+    world.calculate_fitness_score();
+}
+
+// One finished world per seed, the same worlds a network would get on these seeds
+pub fn evaluate_agent(agent: &mut dyn Agent, width: i32, height: i32, seeds: &[u64]) -> Vec<World> {
+    return seeds
+        .iter()
+        .map(|seed| {
+            let mut world = evaluation_world(width, height, Network::new_empty(), *seed);
+            play_agent(agent, &mut world);
+            world
+        })
+        .collect();
+}
+
+// Directions the snake can actually take, turning back is ignored by the snake
+pub fn possible_moves(world: &World) -> Vec<Direction> {
+    return DIRECTIONS
+        .into_iter()
+        .filter(|direction| direction.opposite().to_point() != world.snake.dir)
+        .collect();
+}
+
+// Whether the head survives moving onto the point on the next step
+pub fn is_safe(world: &World, point: Point) -> bool {
+    if point.x <= 0 || point.y <= 0 || point.x >= world.width - 1 || point.y >= world.height - 1 {
+        return false;
+    }
+    if world.obstacles.contains(&point) {
+        return false;
+    }
+    // The tail moves away, unless the snake grows on this step
+    let points = &world.snake.points;
+    let tail_id = points.len() - 1;
+    if tail_id > 0 && point == points[tail_id] {
+        return point != world.apple.position;
+    }
+    return !points[..tail_id].contains(&point);
+}
+
+pub fn manhattan_distance(from: Point, to: Point) -> i32 {
+    let offset = (to - from).abs();
+    return offset.x + offset.y;
+}
@@ -0,0 +1,18 @@
+use crate::{
+    agents::Agent,
+    game::{Direction, world::World},
+    perceptron::network::Network,
+};
+
+// A saved network, played exactly like World::tick plays it
+pub struct NetworkAgent {
+    pub network: Network,
+}
+impl Agent for NetworkAgent {
+    fn name(&self) -> &'static str {
+        return "network";
+    }
+    fn choose(&mut self, world: &World) -> Direction {
+        return Direction::from_network(self.network.propagate(world.get_inputs()));
+    }
+}
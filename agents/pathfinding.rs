@@ -0,0 +1,117 @@
+use std::collections::VecDeque;
+
+use crate::{
+    agents::{Agent, DIRECTIONS, greedy::greedy_move, is_safe, possible_moves},
+    game::{Direction, Point, world::World},
+};
+
+/*
+ * Shortest path to the apple, taken only if the snake could still reach its own tail after
+ * eating it, because following the tail can go on forever. Otherwise it chases the tail the
+ * long way around until the apple is safe to go for.
+ */
+pub struct PathfindingAgent;
+impl Agent for PathfindingAgent {
+    fn name(&self) -> &'static str {
+        return "pathfinding";
+    }
+    fn choose(&mut self, world: &World) -> Direction {
+        let head = world.snake.points[0];
+        let body: VecDeque<Point> = world.snake.points.iter().copied().collect();
+        if let Some(path) = find_path(world, &body, world.apple.position) {
+            let after_apple = follow_path(&body, &path, true);
+            let direction = direction_to(head, path[0]);
+            if is_safe(world, path[0])
+                && possible_moves(world).contains(&direction)
+                && tail_distance(world, &after_apple).is_some()
+            {
+                return direction;
+            }
+        }
+        // Stalling for as long as possible leaves the most time for the body to clear the way
+        let tail_chase = possible_moves(world)
+            .into_iter()
+            .filter(|direction| is_safe(world, head + direction.to_point()))
+            .filter_map(|direction| {
+                let next = head + direction.to_point();
+                let after_step = follow_path(&body, &[next], next == world.apple.position);
+                tail_distance(world, &after_step).map(|distance| (direction, distance))
+            })
+            .max_by_key(|(_, distance)| *distance);
+        return match tail_chase {
+            Some((direction, _)) => direction,
+            None => greedy_move(world, true),
+        };
+    }
+}
+
+fn direction_to(from: Point, to: Point) -> Direction {
+    let offset = to - from;
+    return match (offset.x, offset.y) {
+        (0, -1) => Direction::Up,
+        (0, 1) => Direction::Down,
+        (-1, 0) => Direction::Left,
+        _ => Direction::Right,
+    };
+}
+
+// Breadth first search from the head, the body blocks everything but its tail which moves away
+fn find_path(world: &World, body: &VecDeque<Point>, target: Point) -> Option<Vec<Point>> {
+    let width = world.width as usize;
+    let index = |point: Point| point.y as usize * width + point.x as usize;
+    let mut blocked = vec![false; width * world.height as usize];
+    for obstacle in &world.obstacles {
+        blocked[index(*obstacle)] = true;
+    }
+    for point in body.iter().take(body.len() - 1) {
+        blocked[index(*point)] = true;
+    }
+    let start = body[0];
+    let mut previous: Vec<Option<Point>> = vec![None; blocked.len()];
+    blocked[index(start)] = true;
+    let mut queue = VecDeque::from([start]);
+    while let Some(point) = queue.pop_front() {
+        if point == target {
+            let mut path = vec![point];
+            while let Some(before) = previous[index(path[path.len() - 1])] {
+                if before == start {
+                    break;
+                }
+                path.push(before);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for direction in DIRECTIONS {
+            let next = point + direction.to_point();
+            let inside =
+                next.x > 0 && next.y > 0 && next.x < world.width - 1 && next.y < world.height - 1;
+            if inside && !blocked[index(next)] {
+                blocked[index(next)] = true;
+                previous[index(next)] = Some(point);
+                queue.push_back(next);
+            }
+        }
+    }
+    return None;
+}
+
+// Where the body would be after the head walks the path, growing at its end if it eats there
+fn follow_path(body: &VecDeque<Point>, path: &[Point], eats: bool) -> VecDeque<Point> {
+    let mut body = body.clone();
+    for (step_id, point) in path.iter().enumerate() {
+        body.push_front(*point);
+        if !eats || step_id + 1 < path.len() {
+            body.pop_back();
+        }
+    }
+    return body;
+}
+
+fn tail_distance(world: &World, body: &VecDeque<Point>) -> Option<usize> {
+    // A snake this short can't lock itself in
+    if body.len() <= 2 {
+        return Some(0);
+    }
+    return find_path(world, body, body[body.len() - 1]).map(|path| path.len());
+}
@@ -0,0 +1,31 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    agents::{Agent, possible_moves},
+    game::{Direction, world::World},
+};
+
+// Any direction but back, the floor every other agent has to beat
+pub struct RandomAgent {
+    rng: StdRng,
+}
+impl Default for RandomAgent {
+    fn default() -> Self {
+        return Self {
+            rng: StdRng::seed_from_u64(0),
+        };
+    }
+}
+impl Agent for RandomAgent {
+    fn name(&self) -> &'static str {
+        return "random";
+    }
+    // Seeded from the world, so an evaluation plays out the same every time
+    fn reset(&mut self, world: &World) {
+        self.rng = StdRng::seed_from_u64(world.seed);
+    }
+    fn choose(&mut self, world: &World) -> Direction {
+        let moves = possible_moves(world);
+        return moves[self.rng.random_range(0..moves.len())];
+    }
+}
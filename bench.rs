@@ -0,0 +1,191 @@
+use std::{hint::black_box, time::Duration};
+
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use futures::executor::block_on;
+
+use crate::{
+    ELITISTS_NUM, NETWORK_LAYERS, POPULATION_SIZE, TOURNAMENT_SIZE,
+    game::{Direction, Point, snake::Snake, world::World},
+    genetic::{GeneticSettings, crossover::recombine_worlds, selection::get_top_n},
+    perceptron::network::Network,
+};
+
+const BOARD_SIZES: [i32; 3] = [20, 40, 80];
+const POPULATION_SIZES: [usize; 3] = [100, 500, 1000];
+const SNAKE_LENGTHS: [usize; 3] = [4, 32, 256];
+// Every benchmark world starts from the same seed, so runs on two commits see the same boards
+const BENCH_SEED: u64 = 7;
+
+/*
+ * Criterion benchmarks of the simulation hot paths, run with `bench` instead of `cargo bench`
+ * because everything lives in the binary. Results and baselines go to target/criterion, so
+ * `bench --save-baseline before` on one commit and `bench --baseline before` on the next
+ * compares the two.
+ */
+pub struct BenchConfig {
+    pub filter: Option<String>,
+    pub save_baseline: Option<String>,
+    pub baseline: Option<String>,
+    // Fewer samples for a rough number, not for comparisons
+    pub quick: bool,
+}
+impl BenchConfig {
+    // bench [--filter TEXT] [--save-baseline NAME] [--baseline NAME] [--quick]
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = Self {
+            filter: None,
+            save_baseline: None,
+            baseline: None,
+            quick: false,
+        };
+        let mut i = 0;
+        while i < args.len() {
+            if args[i] == "--quick" {
+                config.quick = true;
+                i += 1;
+                continue;
+            }
+            let value = args
+                .get(i + 1)
+                .ok_or(format!("Missing value for {}", args[i]))?;
+            match args[i].as_str() {
+                "--filter" => config.filter = Some(value.clone()),
+                "--save-baseline" => config.save_baseline = Some(value.clone()),
+                "--baseline" => config.baseline = Some(value.clone()),
+                other => return Err(format!("Unknown option {}", other)),
+            }
+            i += 2;
+        }
+        return Ok(config);
+    }
+}
+
+pub fn run_benchmarks(config: BenchConfig) {
+    let mut criterion = Criterion::default();
+    if config.quick {
+        criterion = criterion
+            .sample_size(10)
+            .warm_up_time(Duration::from_millis(200))
+            .measurement_time(Duration::from_secs(1));
+    }
+    if let Some(filter) = config.filter {
+        criterion = criterion.with_filter(filter);
+    }
+    if let Some(name) = config.save_baseline {
+        criterion = criterion.save_baseline(name);
+    }
+    if let Some(name) = config.baseline {
+        criterion = criterion.retain_baseline(name, true);
+    }
+    bench_world(&mut criterion);
+    bench_network(&mut criterion);
+    bench_snake(&mut criterion);
+    bench_generation(&mut criterion);
+    criterion.final_summary();
+}
+
+// A world in the middle of an episode, with a snake long enough for the body sensors to matter
+fn bench_world_of_size(size: i32) -> World {
+    let network = Network::create_random_network(NETWORK_LAYERS.to_vec());
+    let mut world = World::with_seed(size, size, network, BENCH_SEED);
+    let head = Point {
+        x: size / 2,
+        y: size / 2,
+    };
+    world.snake = Snake::new(
+        (0..size / 4)
+            .map(|offset| Point {
+                x: head.x - offset,
+                y: head.y,
+            })
+            .collect(),
+        Point { x: 1, y: 0 },
+    );
+    return world;
+}
+
+fn bench_world(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("world");
+    for size in BOARD_SIZES {
+        let world = bench_world_of_size(size);
+        group.bench_with_input(BenchmarkId::new("tick", size), &world, |b, world| {
+            b.iter_batched(
+                || world.clone(),
+                |world| block_on(world.tick()),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("get_inputs", size), &world, |b, world| {
+            b.iter(|| black_box(world).get_inputs())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("check_snake_collision", size),
+            &world,
+            |b, world| b.iter(|| black_box(world).check_snake_collision()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_network(criterion: &mut Criterion) {
+    let world = bench_world_of_size(40);
+    let inputs = world.get_inputs();
+    criterion.bench_function("network/propagate", |b| {
+        b.iter_batched(
+            || inputs.clone(),
+            |inputs| world.network.propagate(inputs),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_snake(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("snake");
+    for length in SNAKE_LENGTHS {
+        let points = (0..length as i32).map(|x| Point { x: -x, y: 0 }).collect();
+        // Nothing stops the snake, it just keeps going right
+        let mut snake = Snake::new(points, Point { x: 1, y: 0 });
+        group.bench_function(BenchmarkId::new("step", length), |b| {
+            b.iter(|| snake.step(black_box(Direction::Right)))
+        });
+    }
+    group.finish();
+}
+
+// Selection and crossover of one finished generation, the part between two populations
+fn bench_generation(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("generation");
+    group.sample_size(10);
+    for population_size in POPULATION_SIZES {
+        let settings = GeneticSettings {
+            population_size,
+            elitists_num: (ELITISTS_NUM * population_size / POPULATION_SIZE).max(2),
+            tournament_size: (TOURNAMENT_SIZE * population_size / POPULATION_SIZE).max(2),
+            ..Default::default()
+        };
+        let worlds: Vec<World> = (0..population_size)
+            .map(|world_id| {
+                let network = Network::create_random_network(NETWORK_LAYERS.to_vec());
+                let mut world = World::with_seed(40, 40, network, BENCH_SEED + world_id as u64);
+                world.play_to_end();
+                world
+            })
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::new("recombine_and_top_n", population_size),
+            &worlds,
+            |b, worlds| {
+                b.iter_batched(
+                    || worlds.clone(),
+                    |worlds| {
+                        let mut networks = recombine_worlds(&worlds, &settings);
+                        networks.extend(get_top_n(worlds, settings.elitists_num));
+                        networks
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
@@ -0,0 +1,274 @@
+use std::{fs, io, path::PathBuf, thread};
+
+use crate::{
+    FIELD_SIZE,
+    agents::{Agent, AgentKind, evaluate_agent, network::NetworkAgent},
+    game::{
+        evaluation::{EVALUATION_SEEDS_COUNT, evaluation_seeds, evaluation_world},
+        world::{DeathCause, World},
+    },
+    genetic::statistics::median,
+    perceptron::network::Network,
+};
+
+// Anything that can be put on the leaderboard
+pub enum Contestant {
+    Agent(AgentKind),
+    Network(PathBuf),
+}
+impl Contestant {
+    pub fn name(&self) -> String {
+        return match self {
+            Contestant::Agent(kind) => kind.create().name().to_string(),
+            Contestant::Network(path) => path.display().to_string(),
+        };
+    }
+}
+
+pub struct EvalConfig {
+    pub contestants: Vec<Contestant>,
+    pub seeds_count: usize,
+    pub json: Option<PathBuf>,
+}
+impl EvalConfig {
+    // eval [--seeds N] [--json PATH] NETWORK_OR_AGENT...
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = Self {
+            contestants: vec![],
+            seeds_count: EVALUATION_SEEDS_COUNT,
+            json: None,
+        };
+        let mut i = 0;
+        while i < args.len() {
+            // Agent names are taken before paths, a network file called like an agent needs ./
+            if !args[i].starts_with("--") {
+                config
+                    .contestants
+                    .push(match AgentKind::from_name(&args[i]) {
+                        Some(kind) => Contestant::Agent(kind),
+                        None => Contestant::Network(PathBuf::from(&args[i])),
+                    });
+                i += 1;
+                continue;
+            }
+            let value = args
+                .get(i + 1)
+                .ok_or(format!("Missing value for {}", args[i]))?;
+            match args[i].as_str() {
+                "--seeds" => config.seeds_count = value.parse().map_err(|_| "Bad --seeds")?,
+                "--json" => config.json = Some(PathBuf::from(value)),
+                other => return Err(format!("Unknown option {}", other)),
+            }
+            i += 2;
+        }
+        if config.contestants.is_empty() {
+            return Err(String::from(
+                "Nothing to evaluate, give network paths or agent names",
+            ));
+        }
+        return Ok(config);
+    }
+}
+
+// How one contestant has done on every seed
+pub struct LeaderboardEntry {
+    pub name: String,
+    // Apples of every episode, in the order of the seeds
+    pub scores: Vec<usize>,
+    pub wins: usize,
+    pub mean_length: f32,
+    // Indexed the same way as DeathCause::ALL
+    pub deaths: [usize; 4],
+}
+impl LeaderboardEntry {
+    pub fn from_worlds(name: String, worlds: &Vec<World>) -> Self {
+        let mut entry = Self {
+            name,
+            scores: worlds.iter().map(|world| world.score).collect(),
+            wins: worlds.iter().filter(|world| world.has_won()).count(),
+            mean_length: worlds.iter().map(|world| world.life_time).sum::<usize>() as f32
+                / worlds.len().max(1) as f32,
+            deaths: [0; 4],
+        };
+        for world in worlds {
+            if let Some(cause) = world.death_cause {
+                let cause_id = DeathCause::ALL.iter().position(|c| *c == cause).unwrap();
+                entry.deaths[cause_id] += 1;
+            }
+        }
+        return entry;
+    }
+    pub fn mean_apples(&self) -> f32 {
+        return self.scores.iter().sum::<usize>() as f32 / self.scores.len().max(1) as f32;
+    }
+    pub fn median_apples(&self) -> f32 {
+        let mut sorted = self.scores.clone();
+        sorted.sort();
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        return median(&sorted);
+    }
+    pub fn win_rate(&self) -> f32 {
+        return self.wins as f32 / self.scores.len().max(1) as f32;
+    }
+}
+
+/*
+ * Plays every contestant on the same evaluation worlds, so the only difference between two
+ * lines of the leaderboard is who has been playing. Contestants are played in parallel,
+ * the scripted ones on full boards take a while.
+ */
+pub fn run_evaluation(config: &EvalConfig) -> io::Result<Vec<LeaderboardEntry>> {
+    let seeds = evaluation_seeds(config.seeds_count);
+    let mut agents: Vec<(String, Box<dyn Agent>)> = vec![];
+    for contestant in &config.contestants {
+        let agent: Box<dyn Agent> = match contestant {
+            Contestant::Agent(kind) => kind.create(),
+            Contestant::Network(path) => Box::new(NetworkAgent {
+                network: Network::load(path)
+                    .and_then(|network| network.check_playable().map(|_| network))
+                    .map_err(|err| {
+                        io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+                    })?,
+            }),
+        };
+        agents.push((contestant.name(), agent));
+    }
+    let mut entries: Vec<LeaderboardEntry> = thread::scope(|scope| {
+        let handles: Vec<_> = agents
+            .into_iter()
+            .map(|(name, mut agent)| {
+                let seeds = &seeds;
+                scope.spawn(move || {
+                    let worlds = evaluate_agent(agent.as_mut(), FIELD_SIZE, FIELD_SIZE, seeds);
+                    LeaderboardEntry::from_worlds(name, &worlds)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    entries.sort_by(|a, b| {
+        b.mean_apples()
+            .total_cmp(&a.mean_apples())
+            .then(b.win_rate().total_cmp(&a.win_rate()))
+    });
+    if let Some(path) = &config.json {
+        fs::write(path, leaderboard_json(&entries, &seeds))?;
+    }
+    return Ok(entries);
+}
+
+/*
+ * Moves without an apple before starving, on the evaluation worlds and on the training ones.
+ * Scripted agents need the longer evaluation limit, networks have only ever played the shorter one.
+ */
+fn starvation_limits() -> (usize, usize) {
+    let evaluation = evaluation_world(FIELD_SIZE, FIELD_SIZE, Network::new_empty(), 0);
+    return (
+        evaluation.starvation_limit,
+        World::default_starvation_limit(FIELD_SIZE, FIELD_SIZE),
+    );
+}
+
+pub fn print_leaderboard(entries: &[LeaderboardEntry], seeds_count: usize) {
+    let (starvation_limit, training_starvation_limit) = starvation_limits();
+    println!(
+        "Leaderboard, {} seeds on {}x{}, starving after {} moves without an apple ({} in training):",
+        seeds_count, FIELD_SIZE, FIELD_SIZE, starvation_limit, training_starvation_limit
+    );
+    // Network paths can be long, the columns start after the longest name
+    let name_width = entries
+        .iter()
+        .map(|entry| entry.name.len())
+        .max()
+        .unwrap_or(0);
+    for (rank, entry) in entries.iter().enumerate() {
+        let deaths: Vec<String> = DeathCause::ALL
+            .iter()
+            .zip(entry.deaths)
+            .map(|(cause, count)| format!("{} {}", cause.name(), count))
+            .collect();
+        println!(
+            "{:>3}. {:<name_width$} mean apples {:>7.2}, median {:>7.1}, win rate {:>5.1}%, mean length {:>9.1}, deaths: {}",
+            rank + 1,
+            entry.name,
+            entry.mean_apples(),
+            entry.median_apples(),
+            entry.win_rate() * 100.0,
+            entry.mean_length,
+            deaths.join(", ")
+        );
+    }
+}
+
+fn leaderboard_json(entries: &[LeaderboardEntry], seeds: &[u64]) -> String {
+    let entries: Vec<String> = entries
+        .iter()
+        .enumerate()
+        .map(|(rank, entry)| {
+            let deaths: Vec<String> = DeathCause::ALL
+                .iter()
+                .zip(entry.deaths)
+                .map(|(cause, count)| format!("{}:{}", json_string(cause.name()), count))
+                .collect();
+            let scores: Vec<String> = entry.scores.iter().map(|score| score.to_string()).collect();
+            format!(
+                "{{\"rank\":{},\"name\":{},\"mean_apples\":{},\"median_apples\":{},\"win_rate\":{},\"mean_length\":{},\"deaths\":{{{}}},\"scores\":[{}]}}",
+                rank + 1,
+                json_string(&entry.name),
+                entry.mean_apples(),
+                entry.median_apples(),
+                entry.win_rate(),
+                entry.mean_length,
+                deaths.join(","),
+                scores.join(",")
+            )
+        })
+        .collect();
+    let seeds: Vec<String> = seeds.iter().map(|seed| seed.to_string()).collect();
+    let (starvation_limit, training_starvation_limit) = starvation_limits();
+    return format!(
+        "{{\"width\":{},\"height\":{},\"starvation_limit\":{},\"training_starvation_limit\":{},\"seeds\":[{}],\"entries\":[{}]}}\n",
+        FIELD_SIZE,
+        FIELD_SIZE,
+        starvation_limit,
+        training_starvation_limit,
+        seeds.join(","),
+        entries.join(",")
+    );
+}
+// Network paths are the only names that can have quotes, backslashes or control characters in them
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    return escaped;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_strings_escape_quotes_and_control_characters() {
+        assert_eq!(json_string("nets/a.net"), "\"nets/a.net\"");
+        assert_eq!(
+            json_string("a\"b\\c\nd\te\u{1}f\u{1f}"),
+            "\"a\\\"b\\\\c\\nd\\te\\u0001f\\u001f\""
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use crate::game::{recording::EpisodeRecording, snake::Snake};
+
+// The board is split into HISTOGRAM_SIZE x HISTOGRAM_SIZE regions for the head position histogram
+pub const HISTOGRAM_SIZE: usize = 4;
+
+// What the snake has done during an episode, no matter how many apples it has eaten
+#[derive(Clone, PartialEq, Debug)]
+pub struct Behaviour {
+    // Share of ticks the head has spent in each region, row by row
+    pub head_histogram: Vec<f32>,
+    // Share of steps on which the snake has changed its direction
+    pub turn_frequency: f32,
+    // Share of the cells inside the walls the head has visited
+    pub coverage: f32,
+}
+impl Behaviour {
+    // Plays the recorded directions again, only the head path is needed so apples don't matter
+    pub fn from_recording(recording: &EpisodeRecording) -> Self {
+        let width = recording.width.max(1) as usize;
+        let height = recording.height.max(1) as usize;
+        let mut snake = Snake::new(recording.initial_snake.clone(), recording.initial_dir);
+        let mut head_histogram = vec![0.0; HISTOGRAM_SIZE * HISTOGRAM_SIZE];
+        let mut visited = vec![false; width * height];
+        let mut turns = 0;
+        for direction in &recording.directions {
+            let previous_dir = snake.dir;
+            snake.step(*direction);
+            // The very first step only sets the direction of a standing snake
+            if previous_dir.x + previous_dir.y != 0 && snake.dir != previous_dir {
+                turns += 1;
+            }
+            let head = snake.points[0];
+            if head.x < 0 || head.y < 0 || head.x as usize >= width || head.y as usize >= height {
+                continue;
+            }
+            let (x, y) = (head.x as usize, head.y as usize);
+            visited[y * width + x] = true;
+            head_histogram
+                [(y * HISTOGRAM_SIZE / height) * HISTOGRAM_SIZE + x * HISTOGRAM_SIZE / width] +=
+                1.0;
+        }
+        let steps = recording.directions.len().max(1) as f32;
+        head_histogram.iter_mut().for_each(|share| *share /= steps);
+        let inner_cells = (width.saturating_sub(2) * height.saturating_sub(2)).max(1);
+        return Self {
+            head_histogram,
+            turn_frequency: turns as f32 / steps,
+            coverage: (visited.iter().filter(|visited| **visited).count() as f32
+                / inner_cells as f32)
+                .min(1.0),
+        };
+    }
+    // All the measures as one vector, every value is in [0, 1]
+    pub fn descriptor(&self) -> Vec<f32> {
+        let mut descriptor = self.head_histogram.clone();
+        descriptor.push(self.turn_frequency);
+        descriptor.push(self.coverage);
+        return descriptor;
+    }
+}
+
+// Euclidean distance between two behaviour descriptors
+pub fn distance(descriptor1: &[f32], descriptor2: &[f32]) -> f32 {
+    return descriptor1
+        .iter()
+        .zip(descriptor2)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt();
+}
@@ -0,0 +1,177 @@
+use rand::Rng;
+
+use crate::{
+    game::{
+        Direction, Point,
+        world::{DeathCause, StepOutcome, World},
+    },
+    perceptron::network::Network,
+};
+
+pub const ACTION_COUNT: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RewardConfig {
+    pub apple: f32,
+    pub death: f32,
+    // Given on every step, negative values make the agent hurry
+    pub step: f32,
+    // Shaping for moving towards / away from the apple (manhattan distance)
+    pub closer: f32,
+    pub further: f32,
+    // Overrides death reward when the snake has starved
+    pub starvation: Option<f32>,
+}
+impl Default for RewardConfig {
+    fn default() -> Self {
+        return Self {
+            apple: 1.0,
+            death: -1.0,
+            step: 0.0,
+            closer: 0.0,
+            further: 0.0,
+            starvation: None,
+        };
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ActionSpace {
+    pub n: usize,
+}
+impl ActionSpace {
+    pub fn sample(&self, rng: &mut impl Rng) -> Direction {
+        return Direction::from_index(rng.random_range(0..self.n));
+    }
+}
+
+// Every observation has `size` values, each one between its `low` and `high`
+#[derive(Clone, Debug)]
+pub struct ObservationSpace {
+    pub size: usize,
+    pub low: Vec<f32>,
+    pub high: Vec<f32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StepInfo {
+    // None when the episode had already finished before this step
+    pub outcome: Option<StepOutcome>,
+}
+
+// Gym-like wrapper around World so any agent can drive the game, not only the network
+pub struct SnakeEnv {
+    pub world: World,
+    pub width: i32,
+    pub height: i32,
+    pub rewards: RewardConfig,
+    pub starvation_limit: Option<usize>,
+}
+impl SnakeEnv {
+    pub fn new(width: i32, height: i32, rewards: RewardConfig) -> Self {
+        return Self {
+            world: World::with_seed(width, height, Network::new_empty(), 0),
+            width,
+            height,
+            rewards,
+            starvation_limit: None,
+        };
+    }
+    pub fn observation_space(&self) -> ObservationSpace {
+        let (low, high) = World::input_bounds(self.width, self.height);
+        return ObservationSpace {
+            size: World::INPUT_LABELS.len(),
+            low,
+            high,
+        };
+    }
+    pub fn action_space(&self) -> ActionSpace {
+        return ActionSpace { n: ACTION_COUNT };
+    }
+    pub fn reset(&mut self, seed: u64) -> Vec<f32> {
+        self.world = World::with_seed(self.width, self.height, Network::new_empty(), seed);
+        if let Some(starvation_limit) = self.starvation_limit {
+            self.world.set_starvation_limit(starvation_limit);
+        }
+        return self.world.get_inputs();
+    }
+    // Returns (observation, reward, done, info), stepping a finished episode does nothing
+    pub fn step(&mut self, action: Direction) -> (Vec<f32>, f32, bool, StepInfo) {
+        if !self.world.alive {
+            let info = StepInfo { outcome: None };
+            return (self.world.get_inputs(), 0.0, true, info);
+        }
+        let distance_before = apple_distance(&self.world);
+        let outcome = self.world.step(action);
+        let mut reward = self.rewards.step;
+        match outcome {
+            StepOutcome::Ate | StepOutcome::Won => reward += self.rewards.apple,
+            StepOutcome::Died(DeathCause::Starvation) => {
+                reward += self.rewards.starvation.unwrap_or(self.rewards.death)
+            }
+            StepOutcome::Died(_) => reward += self.rewards.death,
+            StepOutcome::Moved => {
+                let distance_after = apple_distance(&self.world);
+                if distance_after < distance_before {
+                    reward += self.rewards.closer;
+                } else if distance_after > distance_before {
+                    reward += self.rewards.further;
+                }
+            }
+        }
+        let info = StepInfo {
+            outcome: Some(outcome),
+        };
+        return (self.world.get_inputs(), reward, !self.world.alive, info);
+    }
+}
+fn apple_distance(world: &World) -> i32 {
+    let diff: Point = (world.snake.points[0] - world.apple.position).abs();
+    return diff.x + diff.y;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inside(space: &ObservationSpace, observation: &[f32]) -> bool {
+        return observation.len() == space.size
+            && observation
+                .iter()
+                .zip(space.low.iter().zip(&space.high))
+                .all(|(value, (low, high))| low <= value && value <= high);
+    }
+
+    #[test]
+    fn observations_stay_inside_the_observation_space() {
+        let mut env = SnakeEnv::new(10, 10, RewardConfig::default());
+        let space = env.observation_space();
+        assert_eq!(space.size, World::INPUT_LABELS.len());
+        assert_eq!(
+            (space.low.len(), space.high.len()),
+            (space.size, space.size)
+        );
+        let mut rng = rand::rng();
+        for seed in 0..20 {
+            let mut observation = env.reset(seed);
+            assert!(inside(&space, &observation), "{:?}", observation);
+            let mut done = false;
+            while !done {
+                (observation, _, done, _) = env.step(env.action_space().sample(&mut rng));
+                assert!(inside(&space, &observation), "{:?}", observation);
+            }
+        }
+    }
+    #[test]
+    fn stepping_a_finished_episode_has_no_outcome() {
+        let mut env = SnakeEnv::new(10, 10, RewardConfig::default());
+        env.reset(1);
+        let mut outcome = None;
+        while env.world.alive {
+            outcome = env.step(Direction::Up).3.outcome;
+        }
+        assert_eq!(outcome, Some(StepOutcome::Died(DeathCause::Wall)));
+        let (_, reward, done, info) = env.step(Direction::Up);
+        assert_eq!((reward, done, info.outcome), (0.0, true, None));
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{game::world::World, perceptron::network::Network};
+
+// Far away from the seeds of training worlds and of the DQN evaluation
+pub const EVALUATION_SEED_OFFSET: u64 = 2_000_000;
+pub const EVALUATION_SEEDS_COUNT: usize = 20;
+
+// The fixed seed set every agent is scored on, so scores of different agents can be compared
+pub fn evaluation_seeds(count: usize) -> Vec<u64> {
+    return (0..count as u64)
+        .map(|seed_id| EVALUATION_SEED_OFFSET + seed_id)
+        .collect();
+}
+
+// A world with the evaluation rules: starving takes as long as walking over the whole board,
+// so an agent that goes around the board for every apple isn't killed for it
+pub fn evaluation_world(width: i32, height: i32, network: Network, seed: u64) -> World {
+    let mut world = World::with_seed(width, height, network, seed);
+    world.set_starvation_limit(world.free_cells());
+    return world;
+}
+
+// One finished evaluation world per seed, fitness already calculated
+pub fn evaluate_network(network: &Network, width: i32, height: i32, seeds: &[u64]) -> Vec<World> {
+    return seeds
+        .iter()
+        .map(|seed| {
+            let mut world = evaluation_world(width, height, network.clone(), *seed);
+            world.play_to_end();
+            world
+        })
+        .collect();
+}
@@ -3,10 +3,15 @@ use std::panic;
 use rand::Rng;
 
 pub mod apple;
+pub mod behaviour;
+pub mod env;
+pub mod evaluation;
+pub mod recording;
+pub mod replay;
 pub mod snake;
 pub mod world;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -38,7 +43,7 @@ impl std::ops::Sub<Point> for Point {
         };
     }
 }
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Direction {
     Up,
     Down,
@@ -56,7 +61,19 @@ impl Direction {
         let max_neuron = (0_usize..4_usize)
             .max_by(|i1, i2| network_output[*i1].total_cmp(&network_output[*i2]))
             .unwrap();
-        return match max_neuron {
+        return Direction::from_index(max_neuron);
+    }
+    // Index of the direction in the network output layer
+    pub fn index(&self) -> usize {
+        return match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+        };
+    }
+    pub fn from_index(index: usize) -> Direction {
+        return match index {
             0 => Direction::Up,
             1 => Direction::Down,
             2 => Direction::Left,
@@ -81,3 +98,62 @@ impl Direction {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIRECTIONS: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    #[test]
+    fn opposite_is_its_own_inverse() {
+        for direction in DIRECTIONS {
+            assert_ne!(direction.opposite(), direction);
+            assert_eq!(direction.opposite().opposite(), direction);
+        }
+    }
+    #[test]
+    fn opposite_points_cancel_out() {
+        for direction in DIRECTIONS {
+            let sum = direction.to_point() + direction.opposite().to_point();
+            assert_eq!(sum, Point { x: 0, y: 0 });
+        }
+    }
+    #[test]
+    fn to_point_is_one_cell_with_y_growing_down() {
+        assert_eq!(Direction::Up.to_point(), Point { x: 0, y: -1 });
+        assert_eq!(Direction::Down.to_point(), Point { x: 0, y: 1 });
+        assert_eq!(Direction::Left.to_point(), Point { x: -1, y: 0 });
+        assert_eq!(Direction::Right.to_point(), Point { x: 1, y: 0 });
+    }
+    #[test]
+    fn index_round_trips() {
+        for (index, direction) in DIRECTIONS.iter().enumerate() {
+            assert_eq!(direction.index(), index);
+            assert_eq!(Direction::from_index(index), *direction);
+        }
+    }
+    #[test]
+    #[should_panic]
+    fn from_index_rejects_a_fifth_direction() {
+        Direction::from_index(4);
+    }
+    #[test]
+    fn from_network_picks_the_strongest_output() {
+        for direction in DIRECTIONS {
+            let mut output = vec![0.1, -0.5, 0.3, 0.2];
+            output[direction.index()] = 0.9;
+            assert_eq!(Direction::from_network(output), direction);
+        }
+    }
+    #[test]
+    #[should_panic]
+    fn from_network_needs_four_outputs() {
+        Direction::from_network(vec![1.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::game::{Direction, Point};
+
+const MAGIC: &[u8; 4] = b"SNKR";
+const FORMAT_VERSION: u8 = 2;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct AppleSpawn {
+    pub tick: usize,
+    pub position: Point,
+}
+
+// Everything needed to play an episode again without the network that played it
+#[derive(PartialEq, Clone, Debug)]
+pub struct EpisodeRecording {
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    pub initial_snake: Vec<Point>,
+    pub initial_dir: Point,
+    pub starvation_limit: usize,
+    pub obstacles: Vec<Point>,
+    pub directions: Vec<Direction>,
+    // The first spawn (tick 0) is the initial apple
+    pub apple_spawns: Vec<AppleSpawn>,
+    pub score: usize,
+    pub life_time: usize,
+}
+impl EpisodeRecording {
+    pub fn new(
+        seed: u64,
+        width: i32,
+        height: i32,
+        initial_snake: Vec<Point>,
+        initial_dir: Point,
+    ) -> Self {
+        return Self {
+            seed,
+            width,
+            height,
+            initial_snake,
+            initial_dir,
+            starvation_limit: 2 * (width + height) as usize,
+            obstacles: vec![],
+            directions: vec![],
+            apple_spawns: vec![],
+            score: 0,
+            life_time: 0,
+        };
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        return writer.flush();
+    }
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        return Self::read_from(&mut reader);
+    }
+    /*
+     * Layout (little endian):
+     * "SNKR", version u8, seed u64, width u16, height u16, initial dir i8 x2,
+     * snake length u32 + points (u16, u16),
+     * starvation limit u32, obstacle count u32 + points (u16, u16),
+     * apple spawn count u32 + spawns (tick u32, x u16, y u16),
+     * direction count u32 + directions packed by 4 into a byte (2 bits each),
+     * score u32, life time u32
+     */
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[FORMAT_VERSION])?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        write_u16(writer, self.width)?;
+        write_u16(writer, self.height)?;
+        writer.write_all(&[
+            self.initial_dir.x as i8 as u8,
+            self.initial_dir.y as i8 as u8,
+        ])?;
+
+        write_u32(writer, self.initial_snake.len())?;
+        for point in &self.initial_snake {
+            write_point(writer, *point)?;
+        }
+
+        write_u32(writer, self.starvation_limit)?;
+        write_u32(writer, self.obstacles.len())?;
+        for point in &self.obstacles {
+            write_point(writer, *point)?;
+        }
+
+        write_u32(writer, self.apple_spawns.len())?;
+        for spawn in &self.apple_spawns {
+            write_u32(writer, spawn.tick)?;
+            write_point(writer, spawn.position)?;
+        }
+
+        write_u32(writer, self.directions.len())?;
+        for chunk in self.directions.chunks(4) {
+            let mut packed = 0_u8;
+            for (i, dir) in chunk.iter().enumerate() {
+                packed |= (dir.index() as u8) << (i * 2);
+            }
+            writer.write_all(&[packed])?;
+        }
+
+        write_u32(writer, self.score)?;
+        write_u32(writer, self.life_time)?;
+        return Ok(());
+    }
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0_u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("Not an episode recording"));
+        }
+        let mut version = [0_u8; 1];
+        reader.read_exact(&mut version)?;
+        // Version 1 had no starvation limit and obstacles
+        if version[0] != 1 && version[0] != FORMAT_VERSION {
+            return Err(invalid_data("Unsupported episode recording version"));
+        }
+        let mut seed = [0_u8; 8];
+        reader.read_exact(&mut seed)?;
+        let seed = u64::from_le_bytes(seed);
+        let width = read_u16(reader)?;
+        let height = read_u16(reader)?;
+        let mut dir = [0_u8; 2];
+        reader.read_exact(&mut dir)?;
+        let initial_dir = Point {
+            x: dir[0] as i8 as i32,
+            y: dir[1] as i8 as i32,
+        };
+
+        // Counts come from the file, so the vectors grow with what is read instead of trusting them
+        let snake_len = read_u32(reader)?;
+        let mut initial_snake = vec![];
+        for _point_id in 0..snake_len {
+            initial_snake.push(read_point(reader)?);
+        }
+
+        let mut starvation_limit = 2 * (width + height) as usize;
+        let mut obstacles = vec![];
+        if version[0] >= 2 {
+            starvation_limit = read_u32(reader)?;
+            let obstacles_len = read_u32(reader)?;
+            for _obstacle_id in 0..obstacles_len {
+                obstacles.push(read_point(reader)?);
+            }
+        }
+
+        let spawns_len = read_u32(reader)?;
+        let mut apple_spawns = vec![];
+        for _spawn_id in 0..spawns_len {
+            let tick = read_u32(reader)?;
+            let position = read_point(reader)?;
+            apple_spawns.push(AppleSpawn { tick, position });
+        }
+
+        let directions_len = read_u32(reader)?;
+        let packed_len = directions_len.div_ceil(4);
+        let mut packed = vec![];
+        reader.take(packed_len as u64).read_to_end(&mut packed)?;
+        if packed.len() != packed_len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        let mut directions = vec![];
+        for dir_id in 0..directions_len {
+            let index = (packed[dir_id / 4] >> ((dir_id % 4) * 2)) & 0b11;
+            directions.push(Direction::from_index(index as usize));
+        }
+
+        let score = read_u32(reader)?;
+        let life_time = read_u32(reader)?;
+        return Ok(Self {
+            seed,
+            width,
+            height,
+            initial_snake,
+            initial_dir,
+            starvation_limit,
+            obstacles,
+            directions,
+            apple_spawns,
+            score,
+            life_time,
+        });
+    }
+}
+fn invalid_data(message: &str) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, message);
+}
+fn write_u16(writer: &mut impl Write, value: i32) -> io::Result<()> {
+    let value = u16::try_from(value).map_err(|_| invalid_data("Value does not fit into u16"))?;
+    return writer.write_all(&value.to_le_bytes());
+}
+fn write_u32(writer: &mut impl Write, value: usize) -> io::Result<()> {
+    let value = u32::try_from(value).map_err(|_| invalid_data("Value does not fit into u32"))?;
+    return writer.write_all(&value.to_le_bytes());
+}
+fn write_point(writer: &mut impl Write, point: Point) -> io::Result<()> {
+    write_u16(writer, point.x)?;
+    return write_u16(writer, point.y);
+}
+fn read_u16(reader: &mut impl Read) -> io::Result<i32> {
+    let mut bytes = [0_u8; 2];
+    reader.read_exact(&mut bytes)?;
+    return Ok(u16::from_le_bytes(bytes) as i32);
+}
+fn read_u32(reader: &mut impl Read) -> io::Result<usize> {
+    let mut bytes = [0_u8; 4];
+    reader.read_exact(&mut bytes)?;
+    return Ok(u32::from_le_bytes(bytes) as usize);
+}
+fn read_point(reader: &mut impl Read) -> io::Result<Point> {
+    let x = read_u16(reader)?;
+    let y = read_u16(reader)?;
+    return Ok(Point { x, y });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        agents::greedy::greedy_move,
+        game::{replay::Replay, world::World},
+        perceptron::network::Network,
+    };
+
+    // A finished episode long enough to have eaten a few apples
+    fn played_recording(obstacles: Vec<Point>) -> EpisodeRecording {
+        let mut world = World::with_seed(12, 12, Network::new_empty(), 42);
+        world.set_obstacles(obstacles);
+        while world.alive {
+            let direction = greedy_move(&world, true);
+            world.step(direction);
+        }
+        assert!(world.score > 0);
+        return world.recording;
+    }
+    fn round_trip(recording: &EpisodeRecording) -> EpisodeRecording {
+        let mut bytes = vec![];
+        recording.write_to(&mut bytes).unwrap();
+        return EpisodeRecording::read_from(&mut bytes.as_slice()).unwrap();
+    }
+
+    #[test]
+    fn saved_recording_loads_and_replays_the_same() {
+        let recording = played_recording(vec![]);
+        let path = std::env::temp_dir().join(format!("recording_test_{}.snkr", std::process::id()));
+        recording.save(&path).unwrap();
+        let loaded = EpisodeRecording::load(&path);
+        let _ = std::fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, recording);
+        let world = Replay::new(loaded).run().unwrap();
+        assert_eq!(world.score, recording.score);
+        assert_eq!(world.life_time, recording.life_time);
+    }
+    #[test]
+    fn recording_with_obstacles_replays_the_same() {
+        let recording = played_recording(vec![Point { x: 3, y: 4 }, Point { x: 8, y: 2 }]);
+        let loaded = round_trip(&recording);
+        assert_eq!(loaded.obstacles, recording.obstacles);
+        let world = Replay::new(loaded).run().unwrap();
+        assert_eq!(world.score, recording.score);
+        assert_eq!(world.life_time, recording.life_time);
+    }
+    #[test]
+    fn version_1_recording_loads_and_replays_the_same() {
+        let recording = played_recording(vec![]);
+        let mut bytes = vec![];
+        recording.write_to(&mut bytes).unwrap();
+        // Version 1 is version 2 without the starvation limit and the obstacle count
+        bytes[4] = 1;
+        let snake_end = 19 + 4 + 4 * recording.initial_snake.len();
+        bytes.drain(snake_end..snake_end + 8);
+        let loaded = EpisodeRecording::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded, recording);
+        let world = Replay::new(loaded).run().unwrap();
+        assert_eq!(world.score, recording.score);
+        assert_eq!(world.life_time, recording.life_time);
+    }
+    #[test]
+    fn huge_counts_in_a_truncated_file_are_rejected() {
+        let recording = played_recording(vec![]);
+        let mut bytes = vec![];
+        recording.write_to(&mut bytes).unwrap();
+        let directions_start = bytes.len() - 8 - recording.directions.len().div_ceil(4) - 4;
+        for count_start in [19, directions_start] {
+            let mut corrupt = bytes.clone();
+            corrupt[count_start..count_start + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            let err = EpisodeRecording::read_from(&mut corrupt.as_slice()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+    #[test]
+    fn unknown_version_is_rejected() {
+        let mut bytes = vec![];
+        played_recording(vec![]).write_to(&mut bytes).unwrap();
+        bytes[4] = FORMAT_VERSION + 1;
+        let err = EpisodeRecording::read_from(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use crate::{
+    game::{Direction, recording::EpisodeRecording, world::World},
+    perceptron::network::Network,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum ReplayError {
+    // The snake ate an apple on a tick where the recording has no spawn
+    MissingAppleSpawn { tick: usize },
+    // The spawn from the recording happened on another tick than in the replay
+    AppleSpawnMismatch { tick: usize, recorded_tick: usize },
+    // The snake died before all of the recorded directions were used
+    EarlyDeath { tick: usize },
+    ScoreMismatch { recorded: usize, replayed: usize },
+    LifeTimeMismatch { recorded: usize, replayed: usize },
+}
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ReplayError::MissingAppleSpawn { tick } => {
+                write!(
+                    f,
+                    "apple was eaten on tick {} but no spawn was recorded",
+                    tick
+                )
+            }
+            ReplayError::AppleSpawnMismatch {
+                tick,
+                recorded_tick,
+            } => write!(
+                f,
+                "apple spawned on tick {} but the recording has it on tick {}",
+                tick, recorded_tick
+            ),
+            ReplayError::EarlyDeath { tick } => {
+                write!(f, "snake died on tick {} before the recording ended", tick)
+            }
+            ReplayError::ScoreMismatch { recorded, replayed } => {
+                write!(f, "recorded score {} but replayed {}", recorded, replayed)
+            }
+            ReplayError::LifeTimeMismatch { recorded, replayed } => {
+                write!(
+                    f,
+                    "recorded life time {} but replayed {}",
+                    recorded, replayed
+                )
+            }
+        };
+    }
+}
+
+// Plays a recorded episode tick by tick, taking the apples from the recording instead of the rng
+pub struct Replay {
+    pub world: World,
+    recording: EpisodeRecording,
+    next_direction: usize,
+}
+impl Replay {
+    pub fn new(recording: EpisodeRecording) -> Self {
+        let mut world = World::with_seed(
+            recording.width,
+            recording.height,
+            Network::new_empty(),
+            recording.seed,
+        );
+        world.set_starvation_limit(recording.starvation_limit);
+        world.set_obstacles(recording.obstacles.clone());
+        world.snake.points = recording.initial_snake.clone();
+        world.snake.dir = recording.initial_dir;
+        if let Some(first_spawn) = recording.apple_spawns.first() {
+            world.apple.position = first_spawn.position;
+            world.recording.apple_spawns[0] = *first_spawn;
+        }
+        world.recording.initial_snake = recording.initial_snake.clone();
+        world.recording.initial_dir = recording.initial_dir;
+        return Self {
+            world,
+            recording,
+            next_direction: 0,
+        };
+    }
+    pub fn next_direction(&self) -> Option<Direction> {
+        return self.recording.directions.get(self.next_direction).copied();
+    }
+    pub fn is_finished(&self) -> bool {
+        return self.next_direction >= self.recording.directions.len();
+    }
+    // Plays the next recorded direction, returns false when there is nothing left to play
+    pub fn tick(&mut self) -> Result<bool, ReplayError> {
+        if self.is_finished() {
+            return Ok(false);
+        }
+        if !self.world.alive {
+            return Err(ReplayError::EarlyDeath {
+                tick: self.world.life_time,
+            });
+        }
+        let spawns_before = self.world.recording.apple_spawns.len();
+        self.world
+            .step(self.recording.directions[self.next_direction]);
+        self.next_direction += 1;
+
+        let spawns_after = self.world.recording.apple_spawns.len();
+        if spawns_after > spawns_before {
+            let spawn_id = spawns_after - 1;
+            let recorded_spawn = match self.recording.apple_spawns.get(spawn_id) {
+                Some(spawn) => *spawn,
+                None => {
+                    return Err(ReplayError::MissingAppleSpawn {
+                        tick: self.world.life_time,
+                    });
+                }
+            };
+            if recorded_spawn.tick != self.world.life_time {
+                return Err(ReplayError::AppleSpawnMismatch {
+                    tick: self.world.life_time,
+                    recorded_tick: recorded_spawn.tick,
+                });
+            }
+            self.world.apple.position = recorded_spawn.position;
+            self.world.recording.apple_spawns[spawn_id] = recorded_spawn;
+        }
+        return Ok(true);
+    }
+    // Plays the whole episode and checks that it ended the same way as recorded
+    pub fn run(mut self) -> Result<World, ReplayError> {
+        while self.tick()? {}
+        if self.world.score != self.recording.score {
+            return Err(ReplayError::ScoreMismatch {
+                recorded: self.recording.score,
+                replayed: self.world.score,
+            });
+        }
+        if self.world.life_time != self.recording.life_time {
+            return Err(ReplayError::LifeTimeMismatch {
+                recorded: self.recording.life_time,
+                replayed: self.world.life_time,
+            });
+        }
+        return Ok(self.world);
+    }
+}
@@ -24,3 +24,97 @@ impl Snake {
         return prev_tail_point;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    fn straight_snake() -> Snake {
+        return Snake::new(
+            vec![
+                Point { x: 5, y: 5 },
+                Point { x: 4, y: 5 },
+                Point { x: 3, y: 5 },
+            ],
+            Point { x: 1, y: 0 },
+        );
+    }
+
+    #[test]
+    fn step_moves_the_head_and_shifts_the_body() {
+        let mut snake = straight_snake();
+        snake.step(Direction::Right);
+        assert_eq!(
+            snake.points,
+            vec![
+                Point { x: 6, y: 5 },
+                Point { x: 5, y: 5 },
+                Point { x: 4, y: 5 },
+            ]
+        );
+    }
+    #[test]
+    fn step_returns_the_previous_tail() {
+        let mut snake = straight_snake();
+        assert_eq!(snake.step(Direction::Up), Point { x: 3, y: 5 });
+        assert_eq!(snake.step(Direction::Up), Point { x: 4, y: 5 });
+    }
+    #[test]
+    fn step_turns() {
+        let mut snake = straight_snake();
+        snake.step(Direction::Down);
+        assert_eq!(snake.dir, Point { x: 0, y: 1 });
+        assert_eq!(snake.points[0], Point { x: 5, y: 6 });
+        assert_eq!(snake.points[1], Point { x: 5, y: 5 });
+    }
+    #[test]
+    fn step_rejects_reversing() {
+        let mut snake = straight_snake();
+        snake.step(Direction::Left);
+        assert_eq!(snake.dir, Point { x: 1, y: 0 });
+        assert_eq!(snake.points[0], Point { x: 6, y: 5 });
+    }
+    #[test]
+    fn single_point_snake_can_start_in_any_direction() {
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            let mut snake = Snake::new(vec![Point { x: 5, y: 5 }], Point { x: 0, y: 0 });
+            let tail = snake.step(direction);
+            assert_eq!(tail, Point { x: 5, y: 5 });
+            assert_eq!(
+                snake.points,
+                vec![Point { x: 5, y: 5 } + direction.to_point()]
+            );
+        }
+    }
+    // Random walks keep the snake in one piece and never turn it around
+    #[test]
+    fn random_steps_keep_the_snake_connected() {
+        for case in 0..200 {
+            let mut rng = StdRng::seed_from_u64(case);
+            let mut snake = straight_snake();
+            for _ in 0..100 {
+                let previous = snake.clone();
+                let tail = snake.step(Direction::from_index(rng.random_range(0..4)));
+
+                assert_ne!(snake.dir, Point { x: 0, y: 0 } - previous.dir);
+                assert_eq!(snake.points[0], previous.points[0] + snake.dir);
+                assert_eq!(
+                    snake.points[1..],
+                    previous.points[..previous.points.len() - 1]
+                );
+                assert_eq!(tail, previous.points[previous.points.len() - 1]);
+                for pair in snake.points.windows(2) {
+                    let gap = (pair[0] - pair[1]).abs();
+                    assert_eq!(gap.x + gap.y, 1);
+                }
+            }
+        }
+    }
+}
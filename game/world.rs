@@ -1,11 +1,51 @@
 use std::f32::consts::SQRT_2;
 
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
 use crate::{
-    game::{Direction, Point, apple::Apple, snake::Snake},
+    game::{
+        Direction, Point,
+        apple::Apple,
+        recording::{AppleSpawn, EpisodeRecording},
+        snake::Snake,
+    },
     perceptron::network::Network,
 };
+// Random apple positions tried before picking from the cells without obstacles
+const APPLE_PLACEMENT_TRIES: usize = 64;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DeathCause {
+    Wall,
+    SelfCollision,
+    Starvation,
+    Obstacle,
+}
+impl DeathCause {
+    pub const ALL: [DeathCause; 4] = [
+        DeathCause::Wall,
+        DeathCause::SelfCollision,
+        DeathCause::Starvation,
+        DeathCause::Obstacle,
+    ];
+    pub fn name(&self) -> &'static str {
+        return match self {
+            DeathCause::Wall => "Wall",
+            DeathCause::SelfCollision => "Self collision",
+            DeathCause::Starvation => "Starvation",
+            DeathCause::Obstacle => "Obstacle",
+        };
+    }
+}
+// What has happened to the snake during one step of the world
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum StepOutcome {
+    Moved,
+    Ate,
+    Died(DeathCause),
+    // Ate the last apple there was room for, the episode is over
+    Won,
+}
 #[derive(PartialEq, Clone)]
 pub struct World {
     pub alive: bool,
@@ -18,10 +58,21 @@ pub struct World {
     pub height: i32,
     pub network: Network,
     pub fitness_score: usize,
+    pub seed: u64,
+    pub recording: EpisodeRecording,
+    // Snake dies if it hasn't eaten an apple for this many ticks
+    pub starvation_limit: usize,
+    pub obstacles: Vec<Point>,
+    pub death_cause: Option<DeathCause>,
+    rng: StdRng,
 }
 
 impl World {
     pub fn new(width: i32, height: i32, network: Network) -> Self {
+        return Self::with_seed(width, height, network, rand::rng().random());
+    }
+    // Same seed gives the same apple spawns as long as the snake makes the same moves
+    pub fn with_seed(width: i32, height: i32, network: Network, seed: u64) -> Self {
         let apple = Apple {
             position: Point { x: 3, y: 3 },
         };
@@ -32,6 +83,7 @@ impl World {
             }],
             Point { x: 0, y: 0 },
         );
+        let recording = EpisodeRecording::new(seed, width, height, snake.points.clone(), snake.dir);
         let mut world = Self {
             alive: true,
             last_apple_time: 0,
@@ -43,60 +95,163 @@ impl World {
             height,
             network,
             fitness_score: 0,
+            seed,
+            recording,
+            starvation_limit: Self::default_starvation_limit(width, height),
+            obstacles: vec![],
+            death_cause: None,
+            rng: StdRng::seed_from_u64(seed),
         };
+        world.recording.starvation_limit = world.starvation_limit;
         world.apple_random_pos();
         return world;
     }
+    pub fn default_starvation_limit(width: i32, height: i32) -> usize {
+        return 2 * (width + height) as usize;
+    }
+    pub fn set_starvation_limit(&mut self, starvation_limit: usize) {
+        self.starvation_limit = starvation_limit;
+        self.recording.starvation_limit = starvation_limit;
+    }
+    // Obstacles have to be placed before the first step, they are a part of the initial state
+    pub fn set_obstacles(&mut self, obstacles: Vec<Point>) {
+        self.obstacles = obstacles;
+        self.recording.obstacles = self.obstacles.clone();
+        if self.obstacles.contains(&self.apple.position) {
+            self.recording.apple_spawns.pop();
+            self.apple_random_pos();
+        }
+    }
     pub async fn tick(mut self) -> Self {
         if self.alive {
-            self.life_time += 1;
-            self.last_apple_time += 1;
             let network_answer = self.network.propagate(self.get_inputs());
             let new_dir = Direction::from_network(network_answer);
+            self.step(new_dir);
+        }
+        return self;
+    }
+    // Lets the network play until the snake dies, outside of the lockstep training loop
+    pub fn play_to_end(&mut self) {
+        while self.alive {
+            let network_answer = self.network.propagate(self.get_inputs());
+            self.step(Direction::from_network(network_answer));
+        }
+        self.calculate_fitness_score();
+    }
+    // Moves the snake in the given direction, no matter who has chosen it
+    pub fn step(&mut self, new_dir: Direction) -> StepOutcome {
+        self.life_time += 1;
+        self.last_apple_time += 1;
+        self.recording.directions.push(new_dir);
 
-            let ghost_tail = self.snake.step(new_dir);
-            if self.snake.points[0] == self.apple.position {
-                self.snake.points.push(ghost_tail);
-                self.score += 1;
-                self.last_apple_time = 0;
-                self.apple_random_pos();
-            }
-            if self.check_snake_collision() {
+        let mut outcome = StepOutcome::Moved;
+        let ghost_tail = self.snake.step(new_dir);
+        if self.snake.points[0] == self.apple.position {
+            self.snake.points.push(ghost_tail);
+            self.score += 1;
+            self.last_apple_time = 0;
+            self.apple_random_pos();
+            outcome = StepOutcome::Ate;
+        }
+        if let Some(cause) = self.check_snake_collision() {
+            outcome = StepOutcome::Died(cause);
+        } else if self.has_won() {
+            outcome = StepOutcome::Won;
+        } else if self.last_apple_time >= self.starvation_limit {
+            outcome = StepOutcome::Died(DeathCause::Starvation);
+        }
+        match outcome {
+            StepOutcome::Died(cause) => {
                 self.alive = false;
+                self.death_cause = Some(cause);
             }
+            StepOutcome::Won => self.alive = false,
+            _ => {}
         }
-        return self;
+        self.recording.score = self.score;
+        self.recording.life_time = self.life_time;
+        return outcome;
+    }
+    // Cells the snake can be on: everything inside the walls but the obstacles
+    pub fn free_cells(&self) -> usize {
+        return ((self.width - 2) * (self.height - 2)) as usize - self.obstacles.len();
     }
-    fn check_snake_collision(&self) -> bool {
-        let mut collision = false;
+    // The snake fills the whole board, there is nowhere left for it to grow
+    pub fn has_won(&self) -> bool {
+        return self.snake.points.len() >= self.free_cells();
+    }
+    pub fn check_snake_collision(&self) -> Option<DeathCause> {
         let head = self.snake.points[0];
+        // Check snake head collision with walls
+        if head.x == 0 || head.y == 0 || head.x == self.width - 1 || head.y == self.height - 1 {
+            return Some(DeathCause::Wall);
+        }
+        if self.obstacles.contains(&head) {
+            return Some(DeathCause::Obstacle);
+        }
         // Check snake head collision with its body
         for i in 1..self.snake.points.len() {
             if head == self.snake.points[i] {
-                collision = true;
+                return Some(DeathCause::SelfCollision);
             }
         }
-        // Check snake head collision with walls
-        if head.x == 0 || head.y == 0 || head.x == self.width - 1 || head.y == self.height - 1 {
-            collision = true;
-        }
-        return collision;
+        return None;
     }
     /*
-    * Inputs are 29 neurons, each of which is responsible for:
-    * 1,2,3,4 are for wall distance: right, up, left, down respectively
-    * 5,6,7,8 are for diagonal wall distance: right-up, left-up, left-down, right-down respectively
-
-    * 9,10,11,12 are for closest body part distance: right, up, left, down respectively
-    * 13,14,15,16 are for diagonal closest body part distance: right-up, left-up, left-down, right-down respectively
-
-    * 17,18,19,20 are for apple distance: right, up, left, down respectively
-    * 21,22,23,24 are for diagonal apple distance: right-up, left-up, left-down, right-down respectively
-
-    * 25,26,27,28 are the section in which apple is located: right, up, left, down
-    * 29 is the sensor getting the distance to the apple
-    * 30,31 are the dir of snake, x, y
-    */
+     * Inputs are 27 neurons, each of which is responsible for:
+     * 0-7 are for inverse wall distance: right, up, left, down, right-up, left-up, left-down, right-down
+     * 8-15 are for inverse distance to the closest body part on the same rays, 0 when a ray sees none
+     * 16-19 are for inverse apple distance: right, up, left, down, 0 when the apple isn't on the ray
+     * 20-23 are 1 when the apple is left, down, right, up of the head respectively
+     * 24 is the manhattan distance to the apple
+     * 25,26 are the dir of snake, x, y
+     * Every input is finite, an empty ray is 0 rather than an infinite distance
+     */
+    // What every value of get_inputs means, in the same order
+    pub const INPUT_LABELS: [&'static str; 27] = [
+        "Wall right",
+        "Wall up",
+        "Wall left",
+        "Wall down",
+        "Wall right-up",
+        "Wall left-up",
+        "Wall left-down",
+        "Wall right-down",
+        "Body right",
+        "Body up",
+        "Body left",
+        "Body down",
+        "Body right-up",
+        "Body left-up",
+        "Body left-down",
+        "Body right-down",
+        "Apple right",
+        "Apple up",
+        "Apple left",
+        "Apple down",
+        "Apple is left",
+        "Apple is down",
+        "Apple is right",
+        "Apple is up",
+        "Apple distance",
+        "Direction x",
+        "Direction y",
+    ];
+    // Goes up whenever get_inputs changes, a network trained on other inputs plays blind
+    pub const INPUTS_VERSION: u32 = 2;
+    // Lowest and highest value of every input on a board of this size, in get_inputs order
+    pub fn input_bounds(width: i32, height: i32) -> (Vec<f32>, Vec<f32>) {
+        // Inverse distances and the apple quadrant flags
+        let mut low = vec![0.0; 24];
+        let mut high = vec![1.0; 24];
+        // Manhattan distance to the apple, the head may be on a wall once the snake is dead
+        low.push(0.0);
+        high.push((width + height - 4).max(0) as f32);
+        // Direction
+        low.extend([-1.0, -1.0]);
+        high.extend([1.0, 1.0]);
+        return (low, high);
+    }
     pub fn get_inputs(&self) -> Vec<f32> {
         let mut inputs = vec![];
 
@@ -121,143 +276,82 @@ impl World {
         let up = (self.snake.points[0].y) as f32;
         let left = (self.snake.points[0].x) as f32;
         let down = (self.height - self.snake.points[0].y - 1) as f32;
-        distances.push(1.0 / right);
-        distances.push(1.0 / up);
-        distances.push(1.0 / left);
-        distances.push(1.0 / down);
+        distances.push(inverse_distance(right));
+        distances.push(inverse_distance(up));
+        distances.push(inverse_distance(left));
+        distances.push(inverse_distance(down));
         let right_up = right.min(up);
         let left_up = left.min(up);
         let left_down = left.min(down);
         let right_down = right.min(down);
-        distances.push(1.0 / right_up);
-        distances.push(1.0 / left_up);
-        distances.push(1.0 / left_down);
-        distances.push(1.0 / right_down);
+        distances.push(inverse_distance(right_up));
+        distances.push(inverse_distance(left_up));
+        distances.push(inverse_distance(left_down));
+        distances.push(inverse_distance(right_down));
         return distances;
     }
     fn get_body_dist(&self) -> Vec<f32> {
-        let mut distances = vec![];
-        let mut right: f32 = 0.0;
-        let mut up: f32 = 0.0;
-        let mut left: f32 = 0.0;
-        let mut down: f32 = 0.0;
-
-        let mut right_up: f32 = 0.0;
-        let mut left_up: f32 = 0.0;
-        let mut left_down: f32 = 0.0;
-        let mut right_down: f32 = 0.0;
-
-        let snake_points = &self.snake.points;
-        for snake_point in 1..snake_points.len() {
-            if snake_points[snake_point].y == snake_points[0].y {
-                if snake_points[snake_point].x > snake_points[0].x {
-                    right = right.min((snake_points[snake_point].x - snake_points[0].x) as f32);
-                    if right == 0.0 {
-                        right = (snake_points[snake_point].x - snake_points[0].x) as f32
-                    }
-                } else {
-                    left = left.min((snake_points[0].x - snake_points[snake_point].x) as f32);
-                    if left == 0.0 {
-                        left = (snake_points[0].x - snake_points[snake_point].x) as f32;
-                    }
-                }
-            }
-            if snake_points[snake_point].x == snake_points[0].x {
-                if snake_points[snake_point].y > snake_points[0].y {
-                    down = down.min((snake_points[snake_point].y - snake_points[0].y) as f32);
-                    if down == 0.0 {
-                        down = (snake_points[snake_point].y - snake_points[0].y) as f32;
-                    }
-                } else {
-                    up = up.min((snake_points[0].y - snake_points[snake_point].y) as f32);
-                    if up == 0.0 {
-                        up = (snake_points[0].y - snake_points[snake_point].y) as f32;
-                    }
-                }
-            }
-            let body_vec = snake_points[0] - snake_points[snake_point];
-            if body_vec.x.abs() == body_vec.y.abs() {
-                if body_vec.x > 0 && body_vec.y < 0 {
-                    right_up = right_up.min((body_vec.x) as f32);
-                    if right_up == 0.0 {
-                        right_up = body_vec.x as f32;
-                    }
-                }
-                if body_vec.x < 0 && body_vec.y < 0 {
-                    left_up = left_up.min(-(body_vec.x) as f32);
-                    if right_up == 0.0 {
-                        right_up = (-body_vec.x) as f32;
-                    }
-                }
-                if body_vec.x < 0 && body_vec.y > 0 {
-                    left_down = left_down.min((body_vec.y) as f32);
-                    if right_up == 0.0 {
-                        right_up = body_vec.y as f32;
-                    }
-                }
-                if body_vec.x > 0 && body_vec.y > 0 {
-                    right_down = right_down.min((body_vec.y) as f32);
-                    if right_up == 0.0 {
-                        right_up = body_vec.y as f32;
-                    }
-                }
+        let head = self.snake.points[0];
+        // Closest body part on every ray, zero while the ray doesn't see any
+        let mut closest = [0.0; 8];
+        let rays = self.snake.points[1..]
+            .iter()
+            .filter_map(|body_point| ray_towards(*body_point - head));
+        for (ray_id, distance) in rays {
+            if closest[ray_id] == 0.0 || distance < closest[ray_id] {
+                closest[ray_id] = distance;
             }
         }
-        distances.push(1.0 / right);
-        distances.push(1.0 / up);
-        distances.push(1.0 / left);
-        distances.push(1.0 / down);
-        distances.push(1.0 / right_up);
-        distances.push(1.0 / left_up);
-        distances.push(1.0 / left_down);
-        distances.push(1.0 / right_down);
-        return distances;
+        return closest
+            .iter()
+            .map(|distance| inverse_distance(*distance))
+            .collect();
     }
     fn get_apple_dist(&self) -> Vec<f32> {
-        let mut distances = vec![];
+        let mut distances = vec![0.0; 4];
         let head = self.snake.points[0];
-        let mut right: f32 = 0.0;
-        let mut up: f32 = 0.0;
-        let mut left: f32 = 0.0;
-        let mut down: f32 = 0.0;
-
-        if self.apple.position.y == head.y {
-            if self.apple.position.x > head.x {
-                right = (self.apple.position.x - head.x) as f32;
-            }
-            if self.apple.position.x < head.x {
-                left = (head.x - self.apple.position.x) as f32;
-            }
-        }
-        if self.apple.position.x == head.x {
-            if self.apple.position.y > head.y {
-                down = (self.apple.position.y - head.y) as f32;
-            }
-            if self.apple.position.y < head.y {
-                up = (head.y - self.apple.position.y) as f32;
-            }
+        // Only the straight rays look for the apple
+        let ray = ray_towards(self.apple.position - head).filter(|(ray_id, _)| *ray_id < 4);
+        if let Some((ray_id, distance)) = ray {
+            distances[ray_id] = inverse_distance(distance);
         }
 
         let apple_vec = head - self.apple.position;
-        distances.push(1.0 / right);
-        distances.push(1.0 / up);
-        distances.push(1.0 / left);
-        distances.push(1.0 / down);
-
         distances.push(if apple_vec.x > 0 { 1.0 } else { 0.0 });
         distances.push(if apple_vec.y < 0 { 1.0 } else { 0.0 });
         distances.push(if apple_vec.x < 0 { 1.0 } else { 0.0 });
         distances.push(if apple_vec.y > 0 { 1.0 } else { 0.0 });
-        let apple_dist = (apple_vec.x + apple_vec.y) as f32;
+        let apple_dist = (apple_vec.x.abs() + apple_vec.y.abs()) as f32;
         distances.push(apple_dist);
 
         return distances;
     }
     fn apple_random_pos(&mut self) {
-        let mut rng = rand::rng();
-        let new_x = rng.random_range(1..(self.width - 1));
-        let new_y = rng.random_range(1..(self.height - 1));
-        self.apple.position = Point { x: new_x, y: new_y };
+        let mut placed = false;
+        for _try_id in 0..APPLE_PLACEMENT_TRIES {
+            let new_x = self.rng.random_range(1..(self.width - 1));
+            let new_y = self.rng.random_range(1..(self.height - 1));
+            self.apple.position = Point { x: new_x, y: new_y };
+            if !self.obstacles.contains(&self.apple.position) {
+                placed = true;
+                break;
+            }
+        }
+        // Mostly obstacles, pick one of the cells that are left instead of guessing forever
+        if !placed {
+            let free_cells: Vec<Point> = (1..self.height - 1)
+                .flat_map(|y| (1..self.width - 1).map(move |x| Point { x, y }))
+                .filter(|point| !self.obstacles.contains(point))
+                .collect();
+            if free_cells.is_empty() {
+                return;
+            }
+            self.apple.position = free_cells[self.rng.random_range(0..free_cells.len())];
+        }
+        self.recording.apple_spawns.push(AppleSpawn {
+            tick: self.life_time,
+            position: self.apple.position,
+        });
     }
     pub fn calculate_fitness_score(&mut self) {
         let mut fitness_score = 0;
@@ -266,3 +360,300 @@ impl World {
         self.fitness_score = fitness_score;
     }
 }
+// Nothing seen and being right on top of it both give 0, never an infinity
+fn inverse_distance(distance: f32) -> f32 {
+    if distance <= 0.0 {
+        return 0.0;
+    }
+    return 1.0 / distance;
+}
+// Ray (in get_inputs order: right, up, left, down, right-up, left-up, left-down, right-down)
+// that sees a point this far from the head, and how many cells away it is
+fn ray_towards(offset: Point) -> Option<(usize, f32)> {
+    // Off the straight lines and the diagonals no ray passes through it
+    if offset.x != 0 && offset.y != 0 && offset.x.abs() != offset.y.abs() {
+        return None;
+    }
+    let ray_id = match (offset.x.signum(), offset.y.signum()) {
+        (1, 0) => 0,
+        (0, -1) => 1,
+        (-1, 0) => 2,
+        (0, 1) => 3,
+        (1, -1) => 4,
+        (-1, -1) => 5,
+        (-1, 1) => 6,
+        (1, 1) => 7,
+        _ => return None,
+    };
+    return Some((ray_id, offset.x.abs().max(offset.y.abs()) as f32));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALL_INPUTS: usize = 0;
+    const BODY_INPUTS: usize = 8;
+    const APPLE_INPUTS: usize = 16;
+    const QUADRANT_INPUTS: usize = 20;
+    const APPLE_DISTANCE_INPUT: usize = 24;
+    const DIRECTION_INPUTS: usize = 25;
+
+    fn point(x: i32, y: i32) -> Point {
+        return Point { x, y };
+    }
+    // 10x10 board, so the walls are the cells with x or y of 0 and 9
+    fn world_with(snake: Vec<Point>, dir: Point, apple: Point) -> World {
+        let mut world = World::with_seed(10, 10, Network::new_empty(), 0);
+        world.snake = Snake::new(snake, dir);
+        world.apple.position = apple;
+        return world;
+    }
+    fn assert_inputs(inputs: &[f32], expected: &[f32]) {
+        assert_eq!(inputs.len(), expected.len());
+        for (input, expected) in inputs.iter().zip(expected) {
+            assert!(
+                (input - expected).abs() < 1e-6,
+                "got {:?}, expected {:?}",
+                inputs,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn apple_finds_the_last_cell_without_an_obstacle() {
+        let mut world = World::with_seed(10, 10, Network::new_empty(), 3);
+        let obstacles = (1..9)
+            .flat_map(|y| (1..9).map(move |x| point(x, y)))
+            .filter(|cell| *cell != point(6, 7))
+            .collect();
+        world.set_obstacles(obstacles);
+        assert_eq!(world.apple.position, point(6, 7));
+    }
+    #[test]
+    fn fitness_counts_apples_and_moves_until_death() {
+        let mut world = world_with(vec![point(5, 5)], point(1, 0), point(7, 5));
+        assert_eq!(world.step(Direction::Right), StepOutcome::Moved);
+        assert_eq!(world.step(Direction::Right), StepOutcome::Ate);
+        // Out of the way of the snake
+        world.apple.position = point(2, 2);
+        world.calculate_fitness_score();
+        assert_eq!(world.fitness_score, 100);
+
+        assert_eq!(world.step(Direction::Right), StepOutcome::Moved);
+        assert_eq!(
+            world.step(Direction::Right),
+            StepOutcome::Died(DeathCause::Wall)
+        );
+        world.calculate_fitness_score();
+        assert_eq!(world.fitness_score, 100 + 4 / 4);
+
+        // Starving is scored like any other death
+        let mut world = world_with(vec![point(5, 5)], point(1, 0), point(2, 2));
+        world.set_starvation_limit(8);
+        let directions = [
+            Direction::Up,
+            Direction::Up,
+            Direction::Left,
+            Direction::Left,
+            Direction::Left,
+            Direction::Down,
+            Direction::Down,
+        ];
+        for direction in directions {
+            assert_eq!(world.step(direction), StepOutcome::Moved);
+        }
+        assert_eq!(
+            world.step(Direction::Down),
+            StepOutcome::Died(DeathCause::Starvation)
+        );
+        world.calculate_fitness_score();
+        assert_eq!(world.fitness_score, 8 / 4);
+    }
+    #[test]
+    fn collides_with_every_wall() {
+        for head in [point(0, 5), point(9, 5), point(5, 0), point(5, 9)] {
+            let world = world_with(vec![head], point(0, 0), point(2, 2));
+            assert_eq!(world.check_snake_collision(), Some(DeathCause::Wall));
+        }
+    }
+    #[test]
+    fn cells_next_to_the_walls_are_free() {
+        for head in [point(1, 1), point(8, 1), point(1, 8), point(8, 8)] {
+            let world = world_with(vec![head], point(0, 0), point(4, 4));
+            assert_eq!(world.check_snake_collision(), None);
+        }
+    }
+    #[test]
+    fn collides_with_its_own_body() {
+        let snake = vec![
+            point(5, 5),
+            point(5, 6),
+            point(6, 6),
+            point(6, 5),
+            point(5, 5),
+        ];
+        let world = world_with(snake, point(0, -1), point(2, 2));
+        assert_eq!(
+            world.check_snake_collision(),
+            Some(DeathCause::SelfCollision)
+        );
+    }
+    #[test]
+    fn collides_with_obstacles() {
+        let mut world = world_with(vec![point(5, 5)], point(0, 0), point(2, 2));
+        world.set_obstacles(vec![point(5, 5)]);
+        assert_eq!(world.check_snake_collision(), Some(DeathCause::Obstacle));
+    }
+    #[test]
+    fn stepping_into_the_body_kills() {
+        let snake = vec![
+            point(5, 5),
+            point(5, 6),
+            point(6, 6),
+            point(6, 5),
+            point(6, 4),
+        ];
+        let mut world = world_with(snake, point(0, -1), point(2, 2));
+        assert_eq!(
+            world.step(Direction::Right),
+            StepOutcome::Died(DeathCause::SelfCollision)
+        );
+        assert!(!world.alive);
+    }
+    // The tail moves away on the same tick, so its cell is free
+    #[test]
+    fn stepping_into_the_tail_is_safe() {
+        let snake = vec![point(5, 5), point(5, 6), point(6, 6), point(6, 5)];
+        let mut world = world_with(snake, point(0, -1), point(2, 2));
+        assert_eq!(world.step(Direction::Right), StepOutcome::Moved);
+        assert!(world.alive);
+    }
+
+    #[test]
+    fn there_is_a_label_for_every_input() {
+        let world = world_with(vec![point(4, 4)], point(0, 0), point(2, 2));
+        assert_eq!(world.get_inputs().len(), World::INPUT_LABELS.len());
+    }
+    #[test]
+    fn wall_sensors() {
+        let world = world_with(vec![point(4, 4)], point(0, 0), point(2, 2));
+        // 5 cells to the right and down, 4 to the left and up
+        assert_inputs(
+            &world.get_inputs()[WALL_INPUTS..BODY_INPUTS],
+            &[0.2, 0.25, 0.25, 0.2, 0.25, 0.25, 0.25, 0.2],
+        );
+    }
+    #[test]
+    fn wall_sensors_next_to_a_corner() {
+        let world = world_with(vec![point(1, 1)], point(0, 0), point(4, 4));
+        assert_inputs(
+            &world.get_inputs()[WALL_INPUTS..BODY_INPUTS],
+            &[0.125, 1.0, 1.0, 0.125, 1.0, 1.0, 1.0, 0.125],
+        );
+    }
+    /*
+     * . . . . . . . .
+     * . . B B B . . .    up ray: (4,2) at 2 before (4,1) at 3
+     * . . B B B B B .    right-up ray: (6,2) at 2, left-up ray: (2,2) at 2
+     * . . . . H . B .    right ray: (6,4) at 2, nothing on the left
+     * . . . . B B B .    down ray: (4,5) at 1, right-down ray: (5,5) at 1
+     */
+    #[test]
+    fn body_sensors_see_the_closest_part_on_every_ray() {
+        let snake = vec![
+            point(4, 4),
+            point(4, 5),
+            point(5, 5),
+            point(6, 5),
+            point(6, 4),
+            point(6, 3),
+            point(6, 2),
+            point(5, 2),
+            point(4, 2),
+            point(3, 2),
+            point(2, 2),
+            point(2, 1),
+            point(3, 1),
+            point(4, 1),
+        ];
+        let world = world_with(snake, point(0, -1), point(8, 8));
+        assert_inputs(
+            &world.get_inputs()[BODY_INPUTS..APPLE_INPUTS],
+            &[0.5, 0.5, 0.0, 1.0, 0.5, 0.5, 0.0, 1.0],
+        );
+    }
+    #[test]
+    fn body_sensors_tell_the_diagonals_apart() {
+        let diagonals = [point(6, 2), point(2, 2), point(2, 6), point(6, 6)];
+        for (ray_id, body_point) in diagonals.iter().enumerate() {
+            let world = world_with(vec![point(4, 4), *body_point], point(0, 0), point(8, 1));
+            let mut expected = [0.0; 8];
+            expected[4 + ray_id] = 0.5;
+            assert_inputs(&world.get_inputs()[BODY_INPUTS..APPLE_INPUTS], &expected);
+        }
+    }
+    #[test]
+    fn body_sensors_see_nothing_without_a_body() {
+        let world = world_with(vec![point(4, 4)], point(0, 0), point(2, 2));
+        assert_inputs(&world.get_inputs()[BODY_INPUTS..APPLE_INPUTS], &[0.0; 8]);
+    }
+    #[test]
+    fn apple_straight_up() {
+        let world = world_with(vec![point(4, 4)], point(0, 0), point(4, 1));
+        let inputs = world.get_inputs();
+        assert_inputs(
+            &inputs[APPLE_INPUTS..QUADRANT_INPUTS],
+            &[0.0, 1.0 / 3.0, 0.0, 0.0],
+        );
+        assert_inputs(
+            &inputs[QUADRANT_INPUTS..APPLE_DISTANCE_INPUT],
+            &[0.0, 0.0, 0.0, 1.0],
+        );
+        assert_eq!(inputs[APPLE_DISTANCE_INPUT], 3.0);
+    }
+    #[test]
+    fn apple_to_the_right_and_down() {
+        let world = world_with(vec![point(4, 4)], point(0, 0), point(7, 6));
+        let inputs = world.get_inputs();
+        // Not on a straight ray, only the quadrant and the distance see it
+        assert_inputs(&inputs[APPLE_INPUTS..QUADRANT_INPUTS], &[0.0; 4]);
+        assert_inputs(
+            &inputs[QUADRANT_INPUTS..APPLE_DISTANCE_INPUT],
+            &[0.0, 1.0, 1.0, 0.0],
+        );
+        assert_eq!(inputs[APPLE_DISTANCE_INPUT], 5.0);
+    }
+    #[test]
+    fn apple_on_a_diagonal_is_not_straight_ahead() {
+        let world = world_with(vec![point(4, 4)], point(0, 0), point(2, 2));
+        let inputs = world.get_inputs();
+        assert_inputs(&inputs[APPLE_INPUTS..QUADRANT_INPUTS], &[0.0; 4]);
+        assert_inputs(
+            &inputs[QUADRANT_INPUTS..APPLE_DISTANCE_INPUT],
+            &[1.0, 0.0, 0.0, 1.0],
+        );
+        assert_eq!(inputs[APPLE_DISTANCE_INPUT], 4.0);
+    }
+    #[test]
+    fn direction_inputs() {
+        let world = world_with(vec![point(4, 4)], point(-1, 0), point(2, 2));
+        assert_inputs(&world.get_inputs()[DIRECTION_INPUTS..], &[-1.0, 0.0]);
+    }
+    // Whatever the snake does, the network never gets an infinity or a NaN
+    #[test]
+    fn inputs_stay_finite_in_random_episodes() {
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut world = World::with_seed(10, 10, Network::new_empty(), seed);
+            loop {
+                assert!(world.get_inputs().iter().all(|input| input.is_finite()));
+                if !world.alive {
+                    break;
+                }
+                world.step(Direction::from_index(rng.random_range(0..4)));
+            }
+        }
+    }
+}
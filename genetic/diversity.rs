@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+
+use rand::seq::IteratorRandom;
+
+use crate::game::{
+    behaviour::{Behaviour, distance},
+    world::World,
+};
+
+// Pairwise distances are measured on a random sample, all pairs of 1000 networks take too long
+const DIVERSITY_SAMPLE_SIZE: usize = 200;
+
+#[derive(Clone, Default, Debug)]
+pub struct DiversityStats {
+    // Mean Euclidean distance between the parameter vectors of two networks
+    pub mean_weight_distance: f32,
+    // Distinct networks, exact copies are counted once
+    pub unique_genomes: usize,
+    pub population_size: usize,
+    // Mean distance between the behaviour descriptors of two episodes
+    pub behavioural_diversity: f32,
+}
+impl DiversityStats {
+    pub fn from_worlds(worlds: &Vec<World>) -> Self {
+        let mut genomes = HashSet::new();
+        for world in worlds {
+            let bits: Vec<u32> = world
+                .network
+                .parameters()
+                .iter()
+                .map(|value| value.to_bits())
+                .collect();
+            genomes.insert(bits);
+        }
+        let mut rng = rand::rng();
+        let sample = worlds
+            .iter()
+            .choose_multiple(&mut rng, DIVERSITY_SAMPLE_SIZE);
+        let parameters: Vec<Vec<f32>> = sample
+            .iter()
+            .map(|world| world.network.parameters())
+            .collect();
+        let descriptors: Vec<Vec<f32>> = sample
+            .iter()
+            .map(|world| Behaviour::from_recording(&world.recording).descriptor())
+            .collect();
+        return Self {
+            mean_weight_distance: mean_pairwise_distance(&parameters),
+            unique_genomes: genomes.len(),
+            population_size: worlds.len(),
+            behavioural_diversity: mean_pairwise_distance(&descriptors),
+        };
+    }
+    pub fn unique_share(&self) -> f32 {
+        return self.unique_genomes as f32 / self.population_size.max(1) as f32;
+    }
+    pub fn summary(&self) -> String {
+        return format!(
+            "Diversity: weight distance {:.3}, unique genomes {}/{}, behavioural {:.4}",
+            self.mean_weight_distance,
+            self.unique_genomes,
+            self.population_size,
+            self.behavioural_diversity
+        );
+    }
+}
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StagnationResponse {
+    None,
+    // Mutation rate goes up for a few generations
+    Hypermutation,
+    // Part of the children are replaced with random networks
+    RandomImmigrants,
+    // Everything but the elitists is replaced with random networks
+    Restart,
+}
+impl StagnationResponse {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "none" => Some(StagnationResponse::None),
+            "hypermutation" => Some(StagnationResponse::Hypermutation),
+            "immigrants" => Some(StagnationResponse::RandomImmigrants),
+            "restart" => Some(StagnationResponse::Restart),
+            _ => None,
+        };
+    }
+}
+
+pub struct StagnationConfig {
+    pub response: StagnationResponse,
+    // Diversity has collapsed once fewer of the genomes than this share are unique
+    pub min_unique_share: f32,
+    // Best fitness has plateaued once it hasn't improved for this many generations
+    pub plateau_generations: usize,
+    pub hypermutation_rate: f32,
+    pub hypermutation_generations: usize,
+    pub immigrant_share: f32,
+}
+impl Default for StagnationConfig {
+    fn default() -> Self {
+        return Self {
+            response: StagnationResponse::None,
+            min_unique_share: 0.2,
+            plateau_generations: 30,
+            hypermutation_rate: 0.2,
+            hypermutation_generations: 5,
+            immigrant_share: 0.3,
+        };
+    }
+}
+
+fn mean_pairwise_distance(vectors: &Vec<Vec<f32>>) -> f32 {
+    let mut sum = 0.0;
+    let mut pairs = 0;
+    for i in 0..vectors.len() {
+        for j in (i + 1)..vectors.len() {
+            sum += distance(&vectors[i], &vectors[j]);
+            pairs += 1;
+        }
+    }
+    if pairs == 0 {
+        return 0.0;
+    }
+    return sum / pairs as f32;
+}
@@ -0,0 +1,251 @@
+use std::{fs, io, path::Path};
+
+use crate::{
+    game::{
+        evaluation::{evaluate_network, evaluation_world},
+        world::World,
+    },
+    perceptron::network::Network,
+};
+
+const INDEX_FILE: &str = "index.txt";
+
+#[derive(Clone)]
+pub struct HallOfFameEntry {
+    pub network: Network,
+    // Generation in which the network has played its training episode
+    pub generation: usize,
+    // Fitness of the training episode
+    pub fitness: usize,
+    // Mean fitness over the evaluation seeds, the hall is ranked by it
+    pub evaluated_fitness: f32,
+    // Apples eaten on every evaluation seed
+    pub scores: Vec<usize>,
+}
+impl HallOfFameEntry {
+    pub fn mean_score(&self) -> f32 {
+        return self.scores.iter().sum::<usize>() as f32 / self.scores.len().max(1) as f32;
+    }
+}
+
+/*
+ * The best networks ever seen, kept across generations and runs.
+ * Training fitness depends on the luck of one episode, so candidates are played again on
+ * a fixed seed set, with the same rules as eval, and ranked by that.
+ */
+pub struct HallOfFame {
+    pub capacity: usize,
+    pub width: i32,
+    pub height: i32,
+    pub seeds: Vec<u64>,
+    // Moves without an apple before starving in the evaluation worlds
+    pub starvation_limit: usize,
+    // Best first
+    pub entries: Vec<HallOfFameEntry>,
+}
+impl HallOfFame {
+    pub fn new(capacity: usize, width: i32, height: i32, seeds: Vec<u64>) -> Self {
+        return Self {
+            capacity,
+            width,
+            height,
+            seeds,
+            starvation_limit: evaluation_world(width, height, Network::new_empty(), 0)
+                .starvation_limit,
+            entries: vec![],
+        };
+    }
+    // Evaluates the best `candidates` worlds of a generation, returns how many have got in
+    pub fn consider(&mut self, worlds: &Vec<World>, generation: usize, candidates: usize) -> usize {
+        let mut order: Vec<&World> = worlds.iter().collect();
+        order.sort_by_key(|world| std::cmp::Reverse(world.fitness_score));
+        let mut admitted = 0;
+        for world in order.into_iter().take(candidates) {
+            if self
+                .entries
+                .iter()
+                .any(|entry| entry.network == world.network)
+            {
+                continue;
+            }
+            let mut entry = HallOfFameEntry {
+                network: world.network.clone(),
+                generation,
+                fitness: world.fitness_score,
+                evaluated_fitness: 0.0,
+                scores: vec![],
+            };
+            self.evaluate(&mut entry);
+            if self.entries.len() >= self.capacity
+                && self
+                    .entries
+                    .last()
+                    .is_some_and(|worst| worst.evaluated_fitness >= entry.evaluated_fitness)
+            {
+                continue;
+            }
+            self.entries.push(entry);
+            self.entries
+                .sort_by(|e1, e2| e2.evaluated_fitness.total_cmp(&e1.evaluated_fitness));
+            self.entries.truncate(self.capacity);
+            admitted += 1;
+        }
+        return admitted;
+    }
+    fn evaluate(&self, entry: &mut HallOfFameEntry) {
+        let evaluation = evaluate_network(&entry.network, self.width, self.height, &self.seeds);
+        entry.evaluated_fitness = evaluation
+            .iter()
+            .map(|world| world.fitness_score)
+            .sum::<usize>() as f32
+            / evaluation.len().max(1) as f32;
+        entry.scores = evaluation.iter().map(|world| world.score).collect();
+    }
+    // Networks with the given layout, best first, for seeding a population
+    pub fn networks(&self, layer_sizes: &Vec<usize>) -> Vec<Network> {
+        return self
+            .entries
+            .iter()
+            .filter(|entry| entry.network.layer_sizes() == *layer_sizes)
+            .map(|entry| entry.network.clone())
+            .collect();
+    }
+    /*
+     * A directory with one network file per entry and index.txt:
+     * hall-of-fame <capacity> <width> <height> <starvation limit>
+     * seeds <seed 1> <seed 2> ...
+     * <network file> <generation> <fitness> <evaluated fitness> <score 1> <score 2> ...
+     * An index without the starvation limit is from before the evaluation rules were recorded.
+     */
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let seeds: Vec<String> = self.seeds.iter().map(|seed| seed.to_string()).collect();
+        let mut index = format!(
+            "hall-of-fame {} {} {} {}\nseeds {}\n",
+            self.capacity,
+            self.width,
+            self.height,
+            self.starvation_limit,
+            seeds.join(" ")
+        );
+        for (entry_id, entry) in self.entries.iter().enumerate() {
+            let file_name = format!("entry_{}.net", entry_id);
+            entry.network.save(dir.join(&file_name))?;
+            let scores: Vec<String> = entry.scores.iter().map(|score| score.to_string()).collect();
+            index.push_str(&format!(
+                "{} {} {} {} {}\n",
+                file_name,
+                entry.generation,
+                entry.fitness,
+                entry.evaluated_fitness,
+                scores.join(" ")
+            ));
+        }
+        return fs::write(dir.join(INDEX_FILE), index);
+    }
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let index = fs::read_to_string(dir.join(INDEX_FILE))?;
+        let mut lines = index.lines();
+        let header: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+        if !(4..=5).contains(&header.len()) || header[0] != "hall-of-fame" {
+            return Err(invalid("Not a hall of fame index"));
+        }
+        let parse_error = |_| invalid("Bad hall of fame header");
+        let mut hall_of_fame = Self::new(
+            header[1].parse().map_err(parse_error)?,
+            header[2].parse().map_err(parse_error)?,
+            header[3].parse().map_err(parse_error)?,
+            vec![],
+        );
+        let starvation_limit = match header.get(4) {
+            Some(limit) => Some(limit.parse().map_err(parse_error)?),
+            None => None,
+        };
+        let seeds: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+        if seeds.first() != Some(&"seeds") {
+            return Err(invalid("Missing hall of fame seeds"));
+        }
+        hall_of_fame.seeds = seeds[1..]
+            .iter()
+            .map(|seed| seed.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid("Bad hall of fame seed"))?;
+        for line in lines {
+            let values: Vec<&str> = line.split_whitespace().collect();
+            if values.len() < 4 {
+                return Err(invalid("Bad hall of fame entry"));
+            }
+            let bad_entry = |_| invalid("Bad hall of fame entry");
+            let network = Network::load(dir.join(values[0]))?;
+            network.check_playable()?;
+            hall_of_fame.entries.push(HallOfFameEntry {
+                network,
+                generation: values[1].parse().map_err(bad_entry)?,
+                fitness: values[2].parse().map_err(bad_entry)?,
+                evaluated_fitness: values[3]
+                    .parse()
+                    .map_err(|_| invalid("Bad hall of fame entry"))?,
+                scores: values[4..]
+                    .iter()
+                    .map(|score| score.parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(bad_entry)?,
+            });
+        }
+        // Scored with other rules, so the entries are played again to be comparable with new ones
+        if starvation_limit != Some(hall_of_fame.starvation_limit) {
+            let mut entries = std::mem::take(&mut hall_of_fame.entries);
+            for entry in &mut entries {
+                hall_of_fame.evaluate(entry);
+            }
+            entries.sort_by(|e1, e2| e2.evaluated_fitness.total_cmp(&e1.evaluated_fitness));
+            hall_of_fame.entries = entries;
+        }
+        return Ok(hall_of_fame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NETWORK_LAYERS;
+
+    #[test]
+    fn an_index_scored_with_other_rules_is_evaluated_again() {
+        let dir = std::env::temp_dir().join(format!("hall_of_fame_test_{}", std::process::id()));
+        let mut hall_of_fame = HallOfFame::new(3, 12, 12, vec![1, 2, 3]);
+        let world = World::with_seed(
+            12,
+            12,
+            Network::create_random_network(NETWORK_LAYERS.to_vec()),
+            0,
+        );
+        assert_eq!(hall_of_fame.consider(&vec![world], 1, 1), 1);
+        let evaluated = hall_of_fame.entries[0].clone();
+        hall_of_fame.save(&dir).unwrap();
+
+        let loaded = HallOfFame::load(&dir).unwrap();
+        assert_eq!(loaded.starvation_limit, hall_of_fame.starvation_limit);
+        assert_eq!(loaded.entries[0].scores, evaluated.scores);
+
+        // The old format had no starvation limit in the header
+        let index = fs::read_to_string(dir.join(INDEX_FILE)).unwrap();
+        let (_header, rest) = index.split_once('\n').unwrap();
+        let old_entry = format!("entry_0.net 1 {} 12345 9 9 9\n", evaluated.fitness);
+        let (seeds, _entries) = rest.split_once('\n').unwrap();
+        fs::write(
+            dir.join(INDEX_FILE),
+            format!("hall-of-fame 3 12 12\n{}\n{}", seeds, old_entry),
+        )
+        .unwrap();
+        let loaded = HallOfFame::load(&dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(loaded.entries[0].scores, evaluated.scores);
+        assert_eq!(
+            loaded.entries[0].evaluated_fitness,
+            evaluated.evaluated_fitness
+        );
+    }
+}
@@ -1,3 +1,27 @@
+use crate::{ELITISTS_NUM, MUTATION_RATE, POPULATION_SIZE, TOURNAMENT_SIZE};
+
 pub mod crossover;
+pub mod diversity;
+pub mod hall_of_fame;
 mod mutation;
 pub mod selection;
+pub mod statistics;
+
+// Everything the GA operators need to know about one population
+#[derive(Clone, Copy, Debug)]
+pub struct GeneticSettings {
+    pub population_size: usize,
+    pub elitists_num: usize,
+    pub mutation_rate: f32,
+    pub tournament_size: usize,
+}
+impl Default for GeneticSettings {
+    fn default() -> Self {
+        return Self {
+            population_size: POPULATION_SIZE,
+            elitists_num: ELITISTS_NUM,
+            mutation_rate: MUTATION_RATE,
+            tournament_size: TOURNAMENT_SIZE,
+        };
+    }
+}
@@ -2,17 +2,17 @@ use std::vec;
 
 use rand::seq::IteratorRandom;
 
-use crate::{TOURNAMENT_SIZE, game::world::World, perceptron::network::Network};
+use crate::{game::world::World, perceptron::network::Network};
 
-pub fn tournament_selection(networks: &Vec<World>) -> (&World, &World) {
+pub fn tournament_selection(networks: &Vec<World>, tournament_size: usize) -> (&World, &World) {
     let mut rng = rand::rng();
-    let sample = networks.iter().choose_multiple(&mut rng, TOURNAMENT_SIZE);
+    let sample = networks.iter().choose_multiple(&mut rng, tournament_size);
     let parent1 = *(sample.iter().max_by_key(|i| i.fitness_score).unwrap());
 
-    let mut sample = networks.iter().choose_multiple(&mut rng, TOURNAMENT_SIZE);
+    let mut sample = networks.iter().choose_multiple(&mut rng, tournament_size);
     let mut parent2 = *(sample.iter().max_by_key(|i| i.fitness_score).unwrap());
     while parent2 == parent1 {
-        sample = networks.iter().choose_multiple(&mut rng, TOURNAMENT_SIZE);
+        sample = networks.iter().choose_multiple(&mut rng, tournament_size);
         parent2 = *(sample.iter().max_by_key(|i| i.fitness_score).unwrap());
     }
     return (parent1, parent2);
@@ -0,0 +1,68 @@
+use crate::game::world::{DeathCause, World};
+
+#[derive(Clone, Default, Debug)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best_fitness: usize,
+    pub mean_fitness: f32,
+    pub median_fitness: f32,
+    pub best_apples: usize,
+    pub mean_apples: f32,
+    // Indexed the same way as DeathCause::ALL
+    pub deaths: [usize; 4],
+}
+impl GenerationStats {
+    // Worlds have to be finished and have their fitness calculated
+    pub fn from_worlds(generation: usize, worlds: &Vec<World>) -> Self {
+        let mut stats = Self {
+            generation,
+            ..Default::default()
+        };
+        if worlds.is_empty() {
+            return stats;
+        }
+        let mut fitness_scores: Vec<usize> =
+            worlds.iter().map(|world| world.fitness_score).collect();
+        fitness_scores.sort();
+        stats.best_fitness = fitness_scores[fitness_scores.len() - 1];
+        stats.mean_fitness = fitness_scores.iter().sum::<usize>() as f32 / worlds.len() as f32;
+        stats.median_fitness = median(&fitness_scores);
+        stats.best_apples = worlds.iter().map(|world| world.score).max().unwrap();
+        stats.mean_apples =
+            worlds.iter().map(|world| world.score).sum::<usize>() as f32 / worlds.len() as f32;
+        for world in worlds {
+            if let Some(cause) = world.death_cause {
+                let cause_id = DeathCause::ALL.iter().position(|c| *c == cause).unwrap();
+                stats.deaths[cause_id] += 1;
+            }
+        }
+        return stats;
+    }
+    pub fn deaths_by(&self, cause: DeathCause) -> usize {
+        let cause_id = DeathCause::ALL.iter().position(|c| *c == cause).unwrap();
+        return self.deaths[cause_id];
+    }
+    pub fn summary(&self) -> String {
+        let deaths: Vec<String> = DeathCause::ALL
+            .iter()
+            .map(|cause| format!("{} {}", cause.name(), self.deaths_by(*cause)))
+            .collect();
+        return format!(
+            "Generation {}: best fitness {}, mean {:.1}, median {:.1}, best apples {}, mean apples {:.2}, deaths: {}",
+            self.generation,
+            self.best_fitness,
+            self.mean_fitness,
+            self.median_fitness,
+            self.best_apples,
+            self.mean_apples,
+            deaths.join(", ")
+        );
+    }
+}
+pub fn median(sorted: &Vec<usize>) -> f32 {
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        return (sorted[middle - 1] + sorted[middle]) as f32 / 2.0;
+    }
+    return sorted[middle] as f32;
+}
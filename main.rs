@@ -1,49 +1,479 @@
+mod agents;
+mod bench;
+mod eval;
 mod game;
 mod genetic;
 mod perceptron;
+mod reinforcement;
+mod render;
+mod server;
+mod strategies;
+mod ui;
 
-use std::thread::{self};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
 use futures::future::join_all;
-use macroquad::Window;
-use macroquad::window::{Conf, clear_background, next_frame};
-use macroquad::{
-    color::*,
-    shapes::{draw_rectangle, draw_rectangle_lines},
-    text::draw_text,
-};
-use tokio::sync::mpsc::UnboundedReceiver;
 
 use crate::{
-    game::world::World,
-    genetic::{crossover::recombine_worlds, selection::get_top_n},
-    perceptron::network::Network,
+    agents::AgentKind,
+    game::{
+        env::ACTION_COUNT,
+        evaluation::{EVALUATION_SEEDS_COUNT, evaluation_seeds},
+        recording::EpisodeRecording,
+        replay::Replay,
+        world::World,
+    },
+    genetic::{
+        GeneticSettings,
+        diversity::{DiversityStats, StagnationConfig, StagnationResponse},
+        hall_of_fame::HallOfFame,
+        statistics::GenerationStats,
+    },
+    perceptron::{
+        network::Network,
+        training::{TrainingConfig, accuracy, fit, samples_from_recording},
+    },
+    reinforcement::dqn::{DqnConfig, DqnTrainer},
+    strategies::{
+        SearchStrategy, StrategyKind,
+        cmaes::{CmaesConfig, CmaesStrategy, RestartStrategy},
+        es::{EsConfig, EvolutionStrategy},
+        genetic::GeneticStrategy,
+        islands::{IslandConfig, IslandModel, MigrationTopology},
+        map_elites::{MapElites, MapElitesConfig},
+        novelty::{NoveltyConfig, NoveltySearch},
+    },
+    ui::{
+        GameState, GenerationReport, UiKind,
+        control::ControlState,
+        grid::{GRID_SIDE, WorldThumbnail},
+        open_window,
+        snapshot::WorldSnapshot,
+        terminal::open_terminal,
+    },
 };
 
 pub const POPULATION_SIZE: usize = 1000;
 pub const ELITISTS_NUM: usize = 40;
 pub const MUTATION_RATE: f32 = 0.02;
 pub const TOURNAMENT_SIZE: usize = 100;
+pub const NETWORK_LAYERS: [usize; 4] = [World::INPUT_LABELS.len(), 24, 12, ACTION_COUNT];
+// Used when --strategy isn't given
+const DEFAULT_STRATEGY: StrategyKind = StrategyKind::Genetic;
 
-const SCREEN_SIZE: f32 = 800.0;
-const CELL_SIZE: f32 = 20.0;
+pub const SCREEN_SIZE: f32 = 800.0;
+pub const CELL_SIZE: f32 = 20.0;
 const FIELD_SIZE: i32 = (SCREEN_SIZE / CELL_SIZE) as i32;
+// Episodes of the latest champion and of the best one so far are saved here
+const RECORDINGS_DIR: &str = "recordings";
+// And their networks here
+const CHAMPIONS_DIR: &str = "champions";
+// Latest state of the search strategy, for the ones that can resume from it
+const CHECKPOINTS_DIR: &str = "checkpoints";
+// Best networks of all runs, re-evaluated on the evaluation seeds
+const HALL_OF_FAME_DIR: &str = "hall_of_fame";
+const HALL_OF_FAME_SIZE: usize = 20;
+// Best worlds of every generation that get evaluated for the hall of fame
+const HALL_OF_FAME_CANDIDATES: usize = 5;
+// Generation reports the window may fall behind on before new ones are dropped
+const GENERATION_REPORTS_CAPACITY: usize = 64;
 
 #[tokio::main]
 async fn main() {
-    let mut worlds = vec![];
-    (0..POPULATION_SIZE).for_each(|_| {
-        worlds.push(World::new(
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() == 3 && args[1] == "replay" {
+        replay_episode(&args[2]);
+        return;
+    }
+    // imitate NETWORK [--loss L] [--optimizer O] [--learning-rate R] [--epochs N] RECORDING...
+    if args.len() >= 4 && args[1] == "imitate" {
+        match TrainingConfig::from_args(&args[3..]) {
+            Ok((config, recording_paths)) => {
+                imitate_recordings(&args[2], &recording_paths, &config)
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+        return;
+    }
+    if args.len() >= 3 && args[1] == "dqn" {
+        let mut config = DqnConfig::default();
+        if let Some(episodes) = args.get(3) {
+            match episodes.parse() {
+                Ok(episodes) => config.episodes = episodes,
+                Err(_) => {
+                    eprintln!("Bad episodes count {}", episodes);
+                    return;
+                }
+            }
+        }
+        let mut trainer = DqnTrainer::new(FIELD_SIZE, FIELD_SIZE, config);
+        trainer.train();
+        if let Err(err) = trainer.best_network.save(&args[2]) {
+            eprintln!("Failed to save {}: {}", args[2], err);
+        }
+        return;
+    }
+    if args.len() >= 2 && args[1] == "hall-of-fame" {
+        print_hall_of_fame(Path::new(args.get(2).map_or(HALL_OF_FAME_DIR, |dir| dir)));
+        return;
+    }
+    // baselines [seeds count] [agent...], every agent when none is named
+    if args.len() >= 2 && args[1] == "baselines" {
+        let mut seeds_count = EVALUATION_SEEDS_COUNT;
+        let mut kinds = vec![];
+        for arg in &args[2..] {
+            if let Ok(count) = arg.parse() {
+                seeds_count = count;
+                continue;
+            }
+            match AgentKind::from_name(arg) {
+                Some(kind) => kinds.push(kind),
+                None => {
+                    eprintln!("Unknown agent {}", arg);
+                    return;
+                }
+            }
+        }
+        if kinds.is_empty() {
+            kinds = AgentKind::ALL.to_vec();
+        }
+        // The leaderboard of nothing but the scripted agents
+        let config = eval::EvalConfig {
+            contestants: kinds.into_iter().map(eval::Contestant::Agent).collect(),
+            seeds_count,
+            json: None,
+        };
+        match eval::run_evaluation(&config) {
+            Ok(entries) => eval::print_leaderboard(&entries, seeds_count),
+            Err(err) => eprintln!("Evaluation failed: {}", err),
+        }
+        return;
+    }
+    if args.len() >= 2 && args[1] == "eval" {
+        match eval::EvalConfig::from_args(&args[2..]) {
+            Ok(config) => match eval::run_evaluation(&config) {
+                Ok(entries) => eval::print_leaderboard(&entries, config.seeds_count),
+                Err(err) => eprintln!("Evaluation failed: {}", err),
+            },
+            Err(err) => eprintln!("{}", err),
+        }
+        return;
+    }
+    if args.len() >= 2 && args[1] == "bench" {
+        match bench::BenchConfig::from_args(&args[2..]) {
+            Ok(config) => bench::run_benchmarks(config),
+            Err(err) => eprintln!("{}", err),
+        }
+        return;
+    }
+    if args.len() >= 2 && args[1] == "render" {
+        match render::RenderConfig::from_args(&args[2..]) {
+            Ok(config) => match render::render_episode(&config) {
+                Ok(frames) => println!("Rendered {} frames to {}", frames, config.output.display()),
+                Err(err) => eprintln!("Failed to render the episode: {}", err),
+            },
+            Err(err) => eprintln!("{}", err),
+        }
+        return;
+    }
+    if args.len() >= 2 && args[1] == "serve" {
+        match server::ServerConfig::from_args(&args[2..]) {
+            Ok(config) => {
+                if let Err(err) = server::serve(config) {
+                    eprintln!("Agent server failed: {}", err);
+                }
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+        return;
+    }
+    let mut strategy_kind = DEFAULT_STRATEGY;
+    let mut resume_path = None;
+    let mut layer_sizes = NETWORK_LAYERS.to_vec();
+    let mut cmaes_config = CmaesConfig::default();
+    let mut island_config = IslandConfig::default();
+    // Training stops once a snake eats this many apples
+    let mut target_apples = None;
+    let mut stagnation_config = StagnationConfig::default();
+    // Only the genetic algorithm responds to stagnation
+    let mut stagnation_options = false;
+    // Number of hall of fame networks put into the first generation
+    let mut reseed_count = 0;
+    let mut ui_kind = UiKind::Window;
+    let mut arg_id = 1;
+    while arg_id < args.len() {
+        // Every option takes a value
+        if arg_id + 1 >= args.len() {
+            eprintln!("Missing value for {}", args[arg_id]);
+            return;
+        }
+        match args[arg_id].as_str() {
+            "--strategy" => match StrategyKind::from_name(&args[arg_id + 1]) {
+                Some(kind) => strategy_kind = kind,
+                None => {
+                    eprintln!("Unknown strategy {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--resume" => resume_path = Some(PathBuf::from(&args[arg_id + 1])),
+            // Input and output layers included, e.g. 27,8,4
+            "--layers" => {
+                match args[arg_id + 1]
+                    .split(',')
+                    .map(|size| size.parse())
+                    .collect::<Result<Vec<usize>, _>>()
+                {
+                    Ok(sizes)
+                        if sizes.len() >= 2
+                            && sizes[0] == World::INPUT_LABELS.len()
+                            && sizes.last() == Some(&ACTION_COUNT)
+                            && !sizes.contains(&0) =>
+                    {
+                        layer_sizes = sizes
+                    }
+                    _ => {
+                        eprintln!(
+                            "Bad layer sizes {}, they start with {} inputs and end with {} outputs",
+                            args[arg_id + 1],
+                            World::INPUT_LABELS.len(),
+                            ACTION_COUNT
+                        );
+                        return;
+                    }
+                }
+            }
+            "--restarts" => match RestartStrategy::from_name(&args[arg_id + 1]) {
+                Some(restarts) => cmaes_config.restarts = restarts,
+                None => {
+                    eprintln!("Unknown restart strategy {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--islands" => match args[arg_id + 1]
+                .parse()
+                .ok()
+                .and_then(IslandConfig::with_islands)
+            {
+                Some(mut config) => {
+                    config.migration_interval = island_config.migration_interval;
+                    config.migrants = island_config.migrants;
+                    config.topology = island_config.topology;
+                    island_config = config;
+                }
+                None => {
+                    eprintln!(
+                        "Bad islands count {}, every island needs room for more than its elitists",
+                        args[arg_id + 1]
+                    );
+                    return;
+                }
+            },
+            "--migration-interval" => match args[arg_id + 1].parse::<usize>() {
+                Ok(interval) if interval > 0 => island_config.migration_interval = interval,
+                _ => {
+                    eprintln!("Bad migration interval {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--migrants" => match args[arg_id + 1].parse() {
+                Ok(migrants) => island_config.migrants = migrants,
+                Err(_) => {
+                    eprintln!("Bad migrants count {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--topology" => match MigrationTopology::from_name(&args[arg_id + 1]) {
+                Some(topology) => island_config.topology = topology,
+                None => {
+                    eprintln!("Unknown topology {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--ui" => match UiKind::from_name(&args[arg_id + 1]) {
+                Some(kind) => ui_kind = kind,
+                None => {
+                    eprintln!("Unknown ui {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--on-stagnation" => match StagnationResponse::from_name(&args[arg_id + 1]) {
+                Some(response) => {
+                    stagnation_config.response = response;
+                    stagnation_options = true;
+                }
+                None => {
+                    eprintln!("Unknown stagnation response {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--plateau" => match args[arg_id + 1].parse() {
+                Ok(generations) => {
+                    stagnation_config.plateau_generations = generations;
+                    stagnation_options = true;
+                }
+                Err(_) => {
+                    eprintln!("Bad plateau length {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--reseed" => match args[arg_id + 1].parse() {
+                Ok(count) => reseed_count = count,
+                Err(_) => {
+                    eprintln!("Bad reseed count {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            "--target-apples" => match args[arg_id + 1].parse::<usize>() {
+                Ok(apples) => target_apples = Some(apples),
+                Err(_) => {
+                    eprintln!("Bad target apples {}", args[arg_id + 1]);
+                    return;
+                }
+            },
+            other => eprintln!("Ignoring unknown option {}", other),
+        }
+        arg_id += 2;
+    }
+    if stagnation_options && strategy_kind != StrategyKind::Genetic {
+        eprintln!("--on-stagnation and --plateau only work with the genetic algorithm");
+        return;
+    }
+    if resume_path.is_some() && strategy_kind != StrategyKind::EvolutionStrategies {
+        eprintln!("--resume only works with evolution strategies");
+        return;
+    }
+    let mut strategy: Box<dyn SearchStrategy> = match strategy_kind {
+        StrategyKind::Genetic => {
+            let mut strategy =
+                GeneticStrategy::new(layer_sizes.clone(), GeneticSettings::default());
+            strategy.stagnation = stagnation_config;
+            Box::new(strategy)
+        }
+        StrategyKind::EvolutionStrategies => match &resume_path {
+            Some(path) => match EvolutionStrategy::load_checkpoint(path, EsConfig::default()) {
+                Ok(strategy) => Box::new(strategy),
+                Err(err) => {
+                    eprintln!("Failed to resume from {}: {}", path.display(), err);
+                    return;
+                }
+            },
+            None => Box::new(EvolutionStrategy::new(
+                layer_sizes.clone(),
+                EsConfig::default(),
+            )),
+        },
+        StrategyKind::Cmaes => Box::new(CmaesStrategy::new(layer_sizes.clone(), cmaes_config)),
+        StrategyKind::Islands => Box::new(IslandModel::new(layer_sizes.clone(), island_config)),
+        StrategyKind::Novelty => Box::new(NoveltySearch::new(
+            layer_sizes.clone(),
+            NoveltyConfig::default(),
+        )),
+        StrategyKind::MapElites => Box::new(MapElites::new(
+            layer_sizes.clone(),
+            MapElitesConfig::default(),
+        )),
+    };
+    println!("Training with {}", strategy.name());
+
+    let hall_of_fame_dir = Path::new(HALL_OF_FAME_DIR);
+    let mut hall_of_fame = match HallOfFame::load(hall_of_fame_dir) {
+        Ok(hall_of_fame) => hall_of_fame,
+        // Only a missing hall of fame is started over, a broken one would be overwritten
+        Err(err) if err.kind() == io::ErrorKind::NotFound => HallOfFame::new(
+            HALL_OF_FAME_SIZE,
             FIELD_SIZE,
             FIELD_SIZE,
-            Network::create_random_network(vec![31, 24, 12, 4]),
-        ))
-    });
+            evaluation_seeds(EVALUATION_SEEDS_COUNT),
+        ),
+        Err(err) => {
+            eprintln!("Failed to load {}: {}", hall_of_fame_dir.display(), err);
+            return;
+        }
+    };
+    if reseed_count > 0 {
+        let mut networks = hall_of_fame.networks(&layer_sizes);
+        networks.truncate(reseed_count);
+        println!("Reseeding with {} hall of fame networks", networks.len());
+        strategy.reseed(networks);
+    }
+
+    let _ = std::fs::create_dir_all(RECORDINGS_DIR);
+    let _ = std::fs::create_dir_all(CHAMPIONS_DIR);
+    let _ = std::fs::create_dir_all(CHECKPOINTS_DIR);
+    let checkpoint_path = Path::new(CHECKPOINTS_DIR).join("latest.txt");
+    let mut worlds = new_worlds(strategy.as_mut());
     let mut gen_count = 1;
     let mut top_apples = 0;
-    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-    open_window(rx);
+    // Fitness of the best champion saved in this run
+    let mut best_champion_fitness = None;
+    let training_start = Instant::now();
+    let mut generation_start = Instant::now();
+    let mut episodes_played = 0;
+    // Frames only keep the latest value, reports queue up to a limit
+    let (frames_tx, frames_rx) = tokio::sync::watch::channel(None);
+    let (reports_tx, reports_rx) = tokio::sync::mpsc::channel(GENERATION_REPORTS_CAPACITY);
+    let (control_tx, mut control_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut controls = ControlState::default();
+    // Shared with every frame of the followed world, until another world is followed
+    let mut followed_network: Option<((usize, usize), Arc<Network>)> = None;
+    // Without a window nothing sends commands, the trainer just never gets any
+    match ui_kind {
+        UiKind::Window => open_window(frames_rx, reports_rx, control_tx),
+        UiKind::Terminal => open_terminal(frames_rx, reports_rx, false),
+        UiKind::Ascii => open_terminal(frames_rx, reports_rx, true),
+        UiKind::Headless => {}
+    }
     loop {
+        while let Ok(command) = control_rx.try_recv() {
+            controls.apply(command, strategy.as_mut(), &worlds);
+        }
+        while controls.paused {
+            // No frames are built while paused, the last one shows what the controls are set to
+            let mutation_rate = strategy.mutation_rate();
+            frames_tx.send_modify(|frame| {
+                if let Some(frame) = frame {
+                    frame.paused = controls.paused;
+                    frame.mutation_rate = mutation_rate;
+                    frame.tick_delay = controls.tick_delay;
+                    frame.display_mode = controls.display_mode;
+                }
+            });
+            match control_rx.recv().await {
+                Some(command) => controls.apply(command, strategy.as_mut(), &worlds),
+                // Window is closed, nobody can resume anymore
+                None => controls.paused = false,
+            }
+        }
+        if controls.save_requested {
+            controls.save_requested = false;
+            let path = Path::new(CHECKPOINTS_DIR).join(format!("generation_{}.txt", gen_count));
+            let saved = match strategy.save_checkpoint(&path) {
+                Ok(()) => Ok(format!(
+                    "Saved {} and {}",
+                    path.display(),
+                    hall_of_fame_dir.display()
+                )),
+                // Strategies without a checkpoint still have their best networks in the hall of fame
+                Err(err) if err.kind() == io::ErrorKind::Unsupported => Ok(format!(
+                    "{}, only saved {}",
+                    err,
+                    hall_of_fame_dir.display()
+                )),
+                Err(err) => Err(err),
+            };
+            match saved.and_then(|message| hall_of_fame.save(hall_of_fame_dir).map(|_| message)) {
+                Ok(message) => println!("{}", message),
+                Err(err) => eprintln!("Failed to save the checkpoint: {}", err),
+            }
+        }
+        if !controls.tick_delay.is_zero() {
+            tokio::time::sleep(controls.tick_delay).await;
+        }
         if true {
             let mut all_dead = true;
             // all_dead = false
@@ -60,97 +490,208 @@ async fn main() {
             // join_all(futures).await;
             for world in &mut worlds {
                 // world.tick();
-                if world.last_apple_time >= 2 * (world.width + world.height) as usize {
-                    world.alive = false;
-                }
                 if world.alive {
                     all_dead = false;
                 } else if world.fitness_score == 0 {
                     world.calculate_fitness_score();
                 }
             }
-            let _ = tx.send(GameState {
-                world: worlds[worlds.len() - 1].clone(),
-                top_apples: top_apples,
-                generation: gen_count,
-            });
+            let finished_stats = if all_dead {
+                Some(GenerationStats::from_worlds(gen_count, &worlds))
+            } else {
+                None
+            };
+            let followed_world = controls.followed_world(&worlds);
+            // Without a window nobody looks at the frames, so they aren't even built
+            if !frames_tx.is_closed() {
+                let world = &worlds[followed_world];
+                let cache_key = (gen_count, followed_world);
+                let network = match &followed_network {
+                    Some((key, network)) if *key == cache_key => network.clone(),
+                    _ => {
+                        let network = Arc::new(world.network.clone());
+                        followed_network = Some((cache_key, network.clone()));
+                        network
+                    }
+                };
+                frames_tx.send_replace(Some(GameState {
+                    world: WorldSnapshot::new(world, network),
+                    top_apples: top_apples,
+                    generation: gen_count,
+                    followed_world,
+                    mutation_rate: strategy.mutation_rate(),
+                    tick_delay: controls.tick_delay,
+                    display_mode: controls.display_mode,
+                    paused: controls.paused,
+                    thumbnails: worlds
+                        .iter()
+                        .take(GRID_SIDE * GRID_SIDE)
+                        .map(WorldThumbnail::from_world)
+                        .collect(),
+                }));
+            }
+            // Nobody reads the reports without a window
+            let report_stats = finished_stats.as_ref().filter(|_| !reports_tx.is_closed());
+            if let Some(stats) = report_stats {
+                let champion = worlds.iter().max_by_key(|world| world.fitness_score);
+                // A full queue means the window hangs, the training doesn't wait for it
+                let _ = reports_tx.try_send(GenerationReport {
+                    stats: stats.clone(),
+                    generations_per_second: 1.0 / generation_start.elapsed().as_secs_f32(),
+                    champion: champion.unwrap().clone(),
+                });
+            }
             if all_dead {
                 top_apples = worlds.iter().max_by_key(|world| world.score).unwrap().score;
-                let mut networks = recombine_worlds(&worlds);
-                networks.extend(get_top_n(worlds, ELITISTS_NUM));
-                let mut recombinations: Vec<World> = vec![];
-                for network in networks {
-                    recombinations.push(World::new(FIELD_SIZE, FIELD_SIZE, network));
+                episodes_played += worlds.len();
+                println!("{}", finished_stats.unwrap().summary());
+                let diversity = DiversityStats::from_worlds(&worlds);
+                println!("  {}", diversity.summary());
+                let champion = worlds
+                    .iter()
+                    .max_by_key(|world| world.fitness_score)
+                    .unwrap();
+                // Only two champions are kept, a long run would leave thousands of files
+                save_champion(champion, "latest");
+                if best_champion_fitness.is_none_or(|best| champion.fitness_score > best) {
+                    best_champion_fitness = Some(champion.fitness_score);
+                    save_champion(champion, "best");
+                }
+                let admitted = hall_of_fame.consider(&worlds, gen_count, HALL_OF_FAME_CANDIDATES);
+                if admitted > 0 {
+                    println!(
+                        "  {} new in the hall of fame, best evaluated fitness {:.1}",
+                        admitted, hall_of_fame.entries[0].evaluated_fitness
+                    );
+                    if let Err(err) = hall_of_fame.save(hall_of_fame_dir) {
+                        eprintln!("Failed to save {}: {}", hall_of_fame_dir.display(), err);
+                    }
                 }
-                worlds = recombinations;
+                if target_apples.is_some_and(|target| top_apples >= target) {
+                    println!(
+                        "{} reached {} apples in {} generations, {} episodes, {:.1} seconds",
+                        strategy.name(),
+                        top_apples,
+                        gen_count,
+                        episodes_played,
+                        training_start.elapsed().as_secs_f32()
+                    );
+                    return;
+                }
+                strategy.set_diversity(diversity);
+                strategy.tell(worlds);
+                match strategy.save_checkpoint(&checkpoint_path) {
+                    Err(err) if err.kind() != io::ErrorKind::Unsupported => {
+                        eprintln!("Failed to save {}: {}", checkpoint_path.display(), err)
+                    }
+                    _ => {}
+                }
+                worlds = new_worlds(strategy.as_mut());
                 gen_count += 1;
+                controls.on_new_generation(&worlds);
+                generation_start = Instant::now();
             }
         }
     }
 }
-pub fn open_window(rx: UnboundedReceiver<GameState>) {
-    thread::spawn(|| {
-        Window::from_config(window_conf(), draw(rx));
-    });
+fn save_champion(champion: &World, name: &str) {
+    let recording_path = format!("{}/{}.snkr", RECORDINGS_DIR, name);
+    if let Err(err) = champion.recording.save(&recording_path) {
+        eprintln!("Failed to save {}: {}", recording_path, err);
+    }
+    let network_path = format!("{}/{}.net", CHAMPIONS_DIR, name);
+    if let Err(err) = champion.network.save(&network_path) {
+        eprintln!("Failed to save {}: {}", network_path, err);
+    }
 }
-
-fn window_conf() -> Conf {
-    Conf {
-        window_width: SCREEN_SIZE as i32,
-        window_height: SCREEN_SIZE as i32,
-        window_resizable: false,
-        window_title: String::from("Python perceptron"),
-        ..Default::default()
+// Worlds for the networks the strategy asks for, on its seeds if it has chosen them
+fn new_worlds(strategy: &mut dyn SearchStrategy) -> Vec<World> {
+    let networks = strategy.ask();
+    let seeds = strategy.seeds();
+    let mut worlds = vec![];
+    for (network_id, network) in networks.into_iter().enumerate() {
+        match seeds.as_ref().and_then(|seeds| seeds.get(network_id)) {
+            Some(seed) => worlds.push(World::with_seed(FIELD_SIZE, FIELD_SIZE, network, *seed)),
+            None => worlds.push(World::new(FIELD_SIZE, FIELD_SIZE, network)),
+        }
     }
+    return worlds;
 }
-pub async fn draw(mut rx: UnboundedReceiver<GameState>) {
-    let mut gamestate = rx.blocking_recv().unwrap();
-    loop {
-        clear_background(GRAY);
-        // for world in &worlds {
-        draw_world(&gamestate.world);
-        // }
-        draw_text(
-            &format!("Generation {}", gamestate.generation),
-            0.0,
-            20.0,
-            40.0,
-            BLUE,
-        );
-        draw_text(
-            &format!("Best apples: {}", gamestate.top_apples),
-            0.0,
-            50.0,
-            40.0,
-            BLUE,
+fn print_hall_of_fame(dir: &Path) {
+    let hall_of_fame = match HallOfFame::load(dir) {
+        Ok(hall_of_fame) => hall_of_fame,
+        Err(err) => {
+            eprintln!("Failed to load {}: {}", dir.display(), err);
+            return;
+        }
+    };
+    println!(
+        "Hall of fame, {} seeds on {}x{}:",
+        hall_of_fame.seeds.len(),
+        hall_of_fame.width,
+        hall_of_fame.height
+    );
+    for (rank, entry) in hall_of_fame.entries.iter().enumerate() {
+        println!(
+            "{:>3}. generation {:>5}, fitness {:>6}, evaluated fitness {:>8.1}, mean apples {:.2}, best {}",
+            rank + 1,
+            entry.generation,
+            entry.fitness,
+            entry.evaluated_fitness,
+            entry.mean_score(),
+            entry.scores.iter().max().unwrap_or(&0)
         );
-        if let Ok(new_gamestate) = rx.try_recv() {
-            gamestate = new_gamestate
-        };
-        next_frame().await
     }
 }
-fn draw_world(world: &World) {
-    draw_rectangle_lines(0.0, 0.0, SCREEN_SIZE, SCREEN_SIZE, CELL_SIZE * 2.0, RED);
-    for snake_point in &world.snake.points {
-        draw_rectangle(
-            (snake_point.x as f32) * CELL_SIZE,
-            (snake_point.y as f32) * CELL_SIZE,
-            CELL_SIZE - 1.0,
-            CELL_SIZE - 1.0,
-            YELLOW,
-        );
+fn replay_episode(path: &str) {
+    let recording = match EpisodeRecording::load(path) {
+        Ok(recording) => recording,
+        Err(err) => {
+            eprintln!("Failed to load {}: {}", path, err);
+            return;
+        }
+    };
+    match Replay::new(recording).run() {
+        Ok(world) => println!(
+            "Replay matches the recording: score {}, life time {}",
+            world.score, world.life_time
+        ),
+        Err(err) => eprintln!("Replay diverged from the recording: {}", err),
     }
-    draw_rectangle(
-        (world.apple.position.x as f32) * CELL_SIZE,
-        (world.apple.position.y as f32) * CELL_SIZE,
-        CELL_SIZE - 1.0,
-        CELL_SIZE - 1.0,
-        GREEN,
-    );
 }
-pub struct GameState {
-    world: World,
-    top_apples: usize,
-    generation: usize,
+// Supervised training of a fresh network on recorded episodes, then one test game with it
+fn imitate_recordings(network_path: &str, recording_paths: &[String], config: &TrainingConfig) {
+    let mut samples = vec![];
+    for path in recording_paths {
+        let recording = match EpisodeRecording::load(path) {
+            Ok(recording) => recording,
+            Err(err) => {
+                eprintln!("Failed to load {}: {}", path, err);
+                return;
+            }
+        };
+        match samples_from_recording(&recording) {
+            Ok(recording_samples) => samples.extend(recording_samples),
+            Err(err) => eprintln!("Skipping {}: {}", path, err),
+        }
+    }
+    let mut network = Network::create_random_network(NETWORK_LAYERS.to_vec());
+    let losses = fit(&mut network, &samples, config);
+    for (epoch, loss) in losses.iter().enumerate() {
+        println!("Epoch {}: loss {:.4}", epoch + 1, loss);
+    }
+    println!(
+        "Trained on {} samples, accuracy {:.1}%",
+        samples.len(),
+        accuracy(&network, &samples) * 100.0
+    );
+    let mut world = World::new(FIELD_SIZE, FIELD_SIZE, network.clone());
+    world.play_to_end();
+    println!(
+        "Test game: {} apples in {} ticks",
+        world.score, world.life_time
+    );
+    if let Err(err) = network.save(network_path) {
+        eprintln!("Failed to save {}: {}", network_path, err);
+    }
 }
@@ -12,6 +12,51 @@ impl Layer {
         }
         return outputs;
     }
+    // Same as propagate for the sums weighted_sums has already computed
+    pub fn propagate_sums(&self, weighted_sums: &Vec<f32>) -> Vec<f32> {
+        return weighted_sums
+            .iter()
+            .map(|sum| Neuron::activasion(*sum))
+            .collect();
+    }
+    pub fn weighted_sums(&self, inputs: &Vec<f32>) -> Vec<f32> {
+        return self
+            .neurons
+            .iter()
+            .map(|neuron| neuron.weighted_sum(inputs))
+            .collect();
+    }
+    pub fn parameters_count(&self) -> usize {
+        return self
+            .neurons
+            .iter()
+            .map(|neuron| neuron.parameters_count())
+            .sum();
+    }
+    // Fills this layer's slice of the gradients and returns the gradients of the inputs
+    pub fn backward(
+        &self,
+        inputs: &Vec<f32>,
+        weighted_sums: &Vec<f32>,
+        output_gradients: &Vec<f32>,
+        gradients: &mut [f32],
+    ) -> Vec<f32> {
+        let mut input_gradients = vec![0.0; inputs.len()];
+        let mut offset = 0;
+        for neuron_id in 0..self.neurons.len() {
+            let neuron = &self.neurons[neuron_id];
+            let count = neuron.parameters_count();
+            neuron.backward(
+                inputs,
+                weighted_sums[neuron_id],
+                output_gradients[neuron_id],
+                &mut gradients[offset..offset + count],
+                &mut input_gradients,
+            );
+            offset += count;
+        }
+        return input_gradients;
+    }
     pub fn new_empty() -> Self {
         Self { neurons: vec![] }
     }
@@ -1,4 +1,4 @@
 pub mod layer;
 pub mod network;
 pub mod neuron;
-
+pub mod training;
@@ -1,6 +1,27 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
 use rand::Rng;
 
-use crate::perceptron::{layer::Layer, neuron::Neuron};
+use crate::{
+    game::{env::ACTION_COUNT, world::World},
+    perceptron::{layer::Layer, neuron::Neuron},
+};
+
+// Everything the backward pass needs to know about a forward pass
+pub struct ForwardTrace {
+    // Inputs of every layer, the last element is the network output
+    pub activations: Vec<Vec<f32>>,
+    pub weighted_sums: Vec<Vec<f32>>,
+}
+impl ForwardTrace {
+    pub fn output(&self) -> &Vec<f32> {
+        return &self.activations[self.activations.len() - 1];
+    }
+}
 
 #[derive(PartialEq, Clone)]
 pub struct Network {
@@ -15,6 +36,208 @@ impl Network {
             .fold(inputs, |input, layer| layer.propagate(input));
         return output;
     }
+    pub fn forward_trace(&self, inputs: Vec<f32>) -> ForwardTrace {
+        let mut trace = ForwardTrace {
+            activations: vec![inputs],
+            weighted_sums: vec![],
+        };
+        for layer in &self.layers {
+            let sums = layer.weighted_sums(&trace.activations[trace.activations.len() - 1]);
+            trace.activations.push(layer.propagate_sums(&sums));
+            trace.weighted_sums.push(sums);
+        }
+        return trace;
+    }
+    // Adds the gradients of all parameters (in the `parameters` order) to `gradients`
+    pub fn backward(
+        &self,
+        trace: &ForwardTrace,
+        output_gradients: Vec<f32>,
+        gradients: &mut Vec<f32>,
+    ) {
+        let mut layer_gradients = output_gradients;
+        let mut end = gradients.len();
+        for layer_id in (0..self.layers.len()).rev() {
+            let layer = &self.layers[layer_id];
+            let start = end - layer.parameters_count();
+            layer_gradients = layer.backward(
+                &trace.activations[layer_id],
+                &trace.weighted_sums[layer_id],
+                &layer_gradients,
+                &mut gradients[start..end],
+            );
+            end = start;
+        }
+    }
+    pub fn parameters_count(&self) -> usize {
+        return self
+            .layers
+            .iter()
+            .map(|layer| layer.parameters_count())
+            .sum();
+    }
+    // All weights and biases in one vector: layer by layer, neuron by neuron, weights then bias
+    pub fn parameters(&self) -> Vec<f32> {
+        let mut parameters = Vec::with_capacity(self.parameters_count());
+        for layer in &self.layers {
+            for neuron in &layer.neurons {
+                parameters.extend(&neuron.weights);
+                parameters.push(neuron.bias);
+            }
+        }
+        return parameters;
+    }
+    pub fn set_parameters(&mut self, parameters: &[f32]) {
+        assert_eq!(
+            parameters.len(),
+            self.parameters_count(),
+            "Parameters count doesn't match the network"
+        );
+        let mut offset = 0;
+        for layer in &mut self.layers {
+            for neuron in &mut layer.neurons {
+                let weights_count = neuron.weights.len();
+                neuron
+                    .weights
+                    .copy_from_slice(&parameters[offset..offset + weights_count]);
+                neuron.bias = parameters[offset + weights_count];
+                offset += weights_count + 1;
+            }
+        }
+    }
+    // Sizes in the same form create_random_network takes them, input layer included
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![];
+        if let Some(first_layer) = self.layers.first() {
+            sizes.push(
+                first_layer
+                    .neurons
+                    .first()
+                    .map_or(0, |neuron| neuron.weights.len()),
+            );
+        }
+        for layer in &self.layers {
+            sizes.push(layer.neurons.len());
+        }
+        return sizes;
+    }
+    // A network that plays takes every world input and gives a value per direction
+    pub fn check_playable(&self) -> io::Result<()> {
+        let sizes = self.layer_sizes();
+        let (inputs, outputs) = (sizes.first().copied(), sizes.last().copied());
+        if inputs != Some(World::INPUT_LABELS.len()) || outputs != Some(ACTION_COUNT) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Network takes {} inputs and gives {} outputs, playing needs {} and {}",
+                    inputs.unwrap_or(0),
+                    outputs.unwrap_or(0),
+                    World::INPUT_LABELS.len(),
+                    ACTION_COUNT
+                ),
+            ));
+        }
+        return Ok(());
+    }
+    /*
+     * Plain text format, one neuron per line:
+     * network <layers count>
+     * inputs <version of the world inputs it has been trained on>
+     * layer <neurons count> <inputs count>
+     * <bias> <weight 1> <weight 2> ...
+     */
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        file.write_all(self.to_text().as_bytes())?;
+        return file.flush();
+    }
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        return Self::from_text(&fs::read_to_string(path)?);
+    }
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "network {}\ninputs {}\n",
+            self.layers.len(),
+            World::INPUTS_VERSION
+        );
+        for layer in &self.layers {
+            let inputs = layer
+                .neurons
+                .first()
+                .map_or(0, |neuron| neuron.weights.len());
+            text.push_str(&format!("layer {} {}\n", layer.neurons.len(), inputs));
+            for neuron in &layer.neurons {
+                text.push_str(&neuron.bias.to_string());
+                for weight in &neuron.weights {
+                    text.push(' ');
+                    text.push_str(&weight.to_string());
+                }
+                text.push('\n');
+            }
+        }
+        return text;
+    }
+    pub fn from_text(text: &str) -> io::Result<Self> {
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut lines = text.lines();
+        let header: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+        if header.len() != 2 || header[0] != "network" {
+            return Err(invalid("Not a network file"));
+        }
+        let layers_count: usize = header[1].parse().map_err(|_| invalid("Bad layers count"))?;
+        // Files without the line are from before the inputs have been versioned
+        let inputs: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+        let inputs_version = match inputs.as_slice() {
+            ["inputs", version] => version.parse().map_err(|_| invalid("Bad inputs version"))?,
+            _ => 1,
+        };
+        if inputs_version != World::INPUTS_VERSION {
+            return Err(invalid(
+                "Network has been trained on other world inputs, it needs to be trained again",
+            ));
+        }
+        let mut network = Self::new_empty();
+        for _layer_id in 0..layers_count {
+            let layer_header: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+            if layer_header.len() != 3 || layer_header[0] != "layer" {
+                return Err(invalid("Bad layer header"));
+            }
+            let neurons_count: usize = layer_header[1]
+                .parse()
+                .map_err(|_| invalid("Bad neurons count"))?;
+            let inputs_count: usize = layer_header[2]
+                .parse()
+                .map_err(|_| invalid("Bad inputs count"))?;
+            // Every layer takes what the previous one puts out
+            let previous_outputs = network.layers.last().map(|layer| layer.neurons.len());
+            if previous_outputs.is_some_and(|outputs| outputs != inputs_count) {
+                return Err(invalid("Layer inputs don't match the previous layer"));
+            }
+            if neurons_count == 0 {
+                return Err(invalid("Empty layer"));
+            }
+            let mut layer = Layer::new_empty();
+            for _neuron_id in 0..neurons_count {
+                let values: Vec<f32> = lines
+                    .next()
+                    .unwrap_or("")
+                    .split_whitespace()
+                    .map(|value| value.parse::<f32>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| invalid("Bad neuron value"))?;
+                if values.len() != inputs_count + 1 {
+                    return Err(invalid("Wrong weights count"));
+                }
+                layer.neurons.push(Neuron {
+                    bias: values[0],
+                    weights: values[1..].to_vec(),
+                });
+            }
+            network.layers.push(layer);
+        }
+        return Ok(network);
+    }
     pub fn new_empty() -> Self {
         Self { layers: vec![] }
     }
@@ -43,3 +266,52 @@ impl Network {
         return network;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_round_trip_keeps_the_network() {
+        let network = Network::create_random_network(vec![5, 3, 4]);
+        let loaded = Network::from_text(&network.to_text()).unwrap();
+        assert!(loaded == network);
+    }
+    #[test]
+    fn layers_that_dont_fit_together_are_rejected() {
+        let mut network = Network::create_random_network(vec![5, 3]);
+        network
+            .layers
+            .extend(Network::create_random_network(vec![2, 4]).layers);
+        let result = Network::from_text(&network.to_text());
+        assert!(result.is_err_and(|err| err.kind() == io::ErrorKind::InvalidData));
+    }
+    #[test]
+    fn only_networks_for_the_world_inputs_play() {
+        let inputs = World::INPUT_LABELS.len();
+        assert!(
+            Network::create_random_network(vec![inputs, 8, ACTION_COUNT])
+                .check_playable()
+                .is_ok()
+        );
+        for sizes in [vec![31, 8, ACTION_COUNT], vec![inputs, 8, 3]] {
+            let result = Network::create_random_network(sizes).check_playable();
+            assert!(result.is_err_and(|err| err.kind() == io::ErrorKind::InvalidData));
+        }
+        assert!(Network::new_empty().check_playable().is_err());
+    }
+    #[test]
+    fn networks_trained_on_other_inputs_are_rejected() {
+        let text = Network::create_random_network(vec![5, 3, 4]).to_text();
+        let unversioned = text.replacen(&format!("inputs {}\n", World::INPUTS_VERSION), "", 1);
+        let older = text.replacen(
+            &format!("inputs {}", World::INPUTS_VERSION),
+            &format!("inputs {}", World::INPUTS_VERSION - 1),
+            1,
+        );
+        for text in [unversioned, older] {
+            let result = Network::from_text(&text);
+            assert!(result.is_err_and(|err| err.kind() == io::ErrorKind::InvalidData));
+        }
+    }
+}
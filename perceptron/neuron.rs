@@ -7,16 +7,48 @@ pub struct Neuron {
 }
 impl Neuron {
     pub fn propagate(&self, input: &Vec<f32>) -> f32 {
+        return Self::activasion(self.weighted_sum(input));
+    }
+    // Value of the neuron before the activation function
+    pub fn weighted_sum(&self, input: &Vec<f32>) -> f32 {
         let mut output = 0.0;
         for i in 0..input.len() {
             output += input[i] * self.weights[i]
         }
-        output = Self::activasion(output + self.bias);
-        return output;
+        return output + self.bias;
+    }
+    pub fn parameters_count(&self) -> usize {
+        return self.weights.len() + 1;
     }
-    fn activasion(value: f32) -> f32 {
+    /*
+     * Backward pass for a single neuron.
+     * `gradients` is this neuron's slice of the network gradients: weights first, then the bias.
+     * Gradients of the inputs are added to `input_gradients`.
+     */
+    pub fn backward(
+        &self,
+        input: &Vec<f32>,
+        weighted_sum: f32,
+        output_gradient: f32,
+        gradients: &mut [f32],
+        input_gradients: &mut Vec<f32>,
+    ) {
+        let sum_gradient = output_gradient * Self::activasion_derivative(weighted_sum);
+        if sum_gradient == 0.0 {
+            return;
+        }
+        for i in 0..input.len() {
+            gradients[i] += sum_gradient * input[i];
+            input_gradients[i] += sum_gradient * self.weights[i];
+        }
+        gradients[self.weights.len()] += sum_gradient;
+    }
+    pub fn activasion(value: f32) -> f32 {
         // return value.tanh();
         // return 1.0 / (1.0 + E.powf(-value));
         return value.max(0.0);
     }
+    fn activasion_derivative(value: f32) -> f32 {
+        return if value > 0.0 { 1.0 } else { 0.0 };
+    }
 }
@@ -0,0 +1,350 @@
+use rand::seq::SliceRandom;
+
+use crate::{
+    game::{
+        Direction,
+        recording::EpisodeRecording,
+        replay::{Replay, ReplayError},
+    },
+    perceptron::network::Network,
+};
+
+// One observation together with the direction the teacher has chosen for it
+#[derive(Clone)]
+pub struct Sample {
+    pub inputs: Vec<f32>,
+    pub action: Direction,
+}
+impl Sample {
+    pub fn new(inputs: Vec<f32>, action: Direction) -> Self {
+        return Self { inputs, action };
+    }
+}
+
+// Replays the recording and pairs every observation with the direction chosen for it
+pub fn samples_from_recording(recording: &EpisodeRecording) -> Result<Vec<Sample>, ReplayError> {
+    let mut samples = vec![];
+    let mut replay = Replay::new(recording.clone());
+    while let Some(action) = replay.next_direction() {
+        samples.push(Sample::new(replay.world.get_inputs(), action));
+        replay.tick()?;
+    }
+    return Ok(samples);
+}
+
+const DEFAULT_LEARNING_RATE: f32 = 0.001;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Loss {
+    // Against the one-hot encoded action
+    MeanSquaredError,
+    // Softmax over the outputs, then the negative log likelihood of the action
+    CrossEntropy,
+}
+impl Loss {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "mse" => Some(Loss::MeanSquaredError),
+            "cross-entropy" => Some(Loss::CrossEntropy),
+            _ => None,
+        };
+    }
+    // Returns the loss and its gradients with respect to the network outputs
+    pub fn evaluate(&self, outputs: &Vec<f32>, action: Direction) -> (f32, Vec<f32>) {
+        let target = action.index();
+        return match self {
+            Loss::MeanSquaredError => {
+                let errors: Vec<f32> = outputs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, output)| output - if i == target { 1.0 } else { 0.0 })
+                    .collect();
+                let loss = errors.iter().map(|error| error * error).sum::<f32>();
+                let gradients = errors
+                    .iter()
+                    .map(|error| 2.0 * error / outputs.len() as f32)
+                    .collect();
+                (loss / outputs.len() as f32, gradients)
+            }
+            Loss::CrossEntropy => {
+                let probabilities = softmax(outputs);
+                let loss = -probabilities[target].max(f32::MIN_POSITIVE).ln();
+                let mut gradients = probabilities;
+                gradients[target] -= 1.0;
+                (loss, gradients)
+            }
+        };
+    }
+}
+pub fn softmax(values: &Vec<f32>) -> Vec<f32> {
+    let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = values.iter().map(|value| (value - max).exp()).collect();
+    let sum: f32 = exps.iter().sum();
+    return exps.iter().map(|value| value / sum).collect();
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum OptimizerKind {
+    Sgd {
+        learning_rate: f32,
+        momentum: f32,
+    },
+    Adam {
+        learning_rate: f32,
+        beta1: f32,
+        beta2: f32,
+        epsilon: f32,
+    },
+}
+impl OptimizerKind {
+    pub fn sgd(learning_rate: f32) -> Self {
+        return OptimizerKind::Sgd {
+            learning_rate,
+            momentum: 0.0,
+        };
+    }
+    pub fn adam(learning_rate: f32) -> Self {
+        return OptimizerKind::Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+        };
+    }
+    pub fn from_name(name: &str, learning_rate: f32) -> Option<Self> {
+        return match name {
+            "sgd" => Some(Self::sgd(learning_rate)),
+            "adam" => Some(Self::adam(learning_rate)),
+            _ => None,
+        };
+    }
+}
+// Gradient descent over the flat parameters vector of a network
+pub struct Optimizer {
+    pub kind: OptimizerKind,
+    first_moment: Vec<f32>,
+    second_moment: Vec<f32>,
+    steps: i32,
+}
+impl Optimizer {
+    pub fn new(kind: OptimizerKind) -> Self {
+        return Self {
+            kind,
+            first_moment: vec![],
+            second_moment: vec![],
+            steps: 0,
+        };
+    }
+    pub fn adam(learning_rate: f32) -> Self {
+        return Self::new(OptimizerKind::adam(learning_rate));
+    }
+    // Moves parameters against the gradients
+    pub fn step(&mut self, parameters: &mut Vec<f32>, gradients: &Vec<f32>) {
+        if self.first_moment.len() != parameters.len() {
+            self.first_moment = vec![0.0; parameters.len()];
+            self.second_moment = vec![0.0; parameters.len()];
+            self.steps = 0;
+        }
+        self.steps += 1;
+        match self.kind {
+            OptimizerKind::Sgd {
+                learning_rate,
+                momentum,
+            } => {
+                for i in 0..parameters.len() {
+                    self.first_moment[i] = momentum * self.first_moment[i] + gradients[i];
+                    parameters[i] -= learning_rate * self.first_moment[i];
+                }
+            }
+            OptimizerKind::Adam {
+                learning_rate,
+                beta1,
+                beta2,
+                epsilon,
+            } => {
+                let correction1 = 1.0 - beta1.powi(self.steps);
+                let correction2 = 1.0 - beta2.powi(self.steps);
+                for i in 0..parameters.len() {
+                    self.first_moment[i] =
+                        beta1 * self.first_moment[i] + (1.0 - beta1) * gradients[i];
+                    self.second_moment[i] =
+                        beta2 * self.second_moment[i] + (1.0 - beta2) * gradients[i] * gradients[i];
+                    let first = self.first_moment[i] / correction1;
+                    let second = self.second_moment[i] / correction2;
+                    parameters[i] -= learning_rate * first / (second.sqrt() + epsilon);
+                }
+            }
+        }
+    }
+}
+
+pub struct TrainingConfig {
+    pub epochs: usize,
+    pub batch_size: usize,
+    pub loss: Loss,
+    pub optimizer: OptimizerKind,
+}
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        return Self {
+            epochs: 20,
+            batch_size: 32,
+            loss: Loss::CrossEntropy,
+            optimizer: OptimizerKind::adam(DEFAULT_LEARNING_RATE),
+        };
+    }
+}
+impl TrainingConfig {
+    // [--loss mse|cross-entropy] [--optimizer sgd|adam] [--learning-rate R] [--epochs N],
+    // returns the config and the arguments that aren't options
+    pub fn from_args(args: &[String]) -> Result<(Self, Vec<String>), String> {
+        let mut config = Self::default();
+        let mut optimizer_name = String::from("adam");
+        let mut learning_rate = DEFAULT_LEARNING_RATE;
+        let mut rest = vec![];
+        let mut i = 0;
+        while i < args.len() {
+            if !args[i].starts_with("--") {
+                rest.push(args[i].clone());
+                i += 1;
+                continue;
+            }
+            let value = args
+                .get(i + 1)
+                .ok_or(format!("Missing value for {}", args[i]))?;
+            match args[i].as_str() {
+                "--loss" => config.loss = Loss::from_name(value).ok_or("Bad --loss")?,
+                "--optimizer" => optimizer_name = value.clone(),
+                "--learning-rate" => {
+                    learning_rate = value.parse().map_err(|_| "Bad --learning-rate")?
+                }
+                "--epochs" => config.epochs = value.parse().map_err(|_| "Bad --epochs")?,
+                other => return Err(format!("Unknown option {}", other)),
+            }
+            i += 2;
+        }
+        config.optimizer =
+            OptimizerKind::from_name(&optimizer_name, learning_rate).ok_or("Bad --optimizer")?;
+        return Ok((config, rest));
+    }
+}
+
+// Supervised training of the network on (observation, action) samples, returns mean loss of every epoch
+pub fn fit(network: &mut Network, samples: &Vec<Sample>, config: &TrainingConfig) -> Vec<f32> {
+    let mut optimizer = Optimizer::new(config.optimizer);
+    let mut parameters = network.parameters();
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    let mut rng = rand::rng();
+    let mut epoch_losses = vec![];
+    for _epoch in 0..config.epochs {
+        order.shuffle(&mut rng);
+        let mut epoch_loss = 0.0;
+        for batch in order.chunks(config.batch_size.max(1)) {
+            let mut gradients = vec![0.0; parameters.len()];
+            for sample_id in batch {
+                let sample = &samples[*sample_id];
+                let trace = network.forward_trace(sample.inputs.clone());
+                let (loss, output_gradients) = config.loss.evaluate(trace.output(), sample.action);
+                epoch_loss += loss;
+                network.backward(&trace, output_gradients, &mut gradients);
+            }
+            for gradient in &mut gradients {
+                *gradient /= batch.len() as f32;
+            }
+            optimizer.step(&mut parameters, &gradients);
+            network.set_parameters(&parameters);
+        }
+        epoch_losses.push(epoch_loss / samples.len().max(1) as f32);
+    }
+    return epoch_losses;
+}
+
+// Share of samples where the network picks the same direction as the teacher
+pub fn accuracy(network: &Network, samples: &Vec<Sample>) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let correct = samples
+        .iter()
+        .filter(|sample| {
+            Direction::from_network(network.propagate(sample.inputs.clone())) == sample.action
+        })
+        .count();
+    return correct as f32 / samples.len() as f32;
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    // Small network with seeded parameters, so no weighted sum sits on the ReLU kink
+    fn seeded_network() -> Network {
+        let mut network = Network::create_random_network(vec![6, 5, 4]);
+        let mut rng = StdRng::seed_from_u64(11);
+        let parameters: Vec<f32> = (0..network.parameters_count())
+            .map(|_| rng.random_range(-1.0..1.0))
+            .collect();
+        network.set_parameters(&parameters);
+        return network;
+    }
+    fn loss_of(network: &Network, inputs: &Vec<f32>, loss: Loss, action: Direction) -> f32 {
+        return loss.evaluate(&network.propagate(inputs.clone()), action).0;
+    }
+
+    #[test]
+    fn backward_matches_finite_differences() {
+        let inputs = vec![0.9, -0.4, 0.3, 0.7, -0.8, 0.5];
+        let step = 1e-3;
+        for loss in [Loss::CrossEntropy, Loss::MeanSquaredError] {
+            let network = seeded_network();
+            let trace = network.forward_trace(inputs.clone());
+            let (_, output_gradients) = loss.evaluate(trace.output(), Direction::Left);
+            let mut gradients = vec![0.0; network.parameters_count()];
+            network.backward(&trace, output_gradients, &mut gradients);
+            // Dead ReLUs everywhere would make the check pass for any backward
+            assert!(
+                gradients
+                    .iter()
+                    .filter(|gradient| gradient.abs() > 1e-3)
+                    .count()
+                    > 10
+            );
+
+            let parameters = network.parameters();
+            for (parameter_id, gradient) in gradients.iter().enumerate() {
+                let mut shifted = network.clone();
+                let mut values = parameters.clone();
+                values[parameter_id] += step;
+                shifted.set_parameters(&values);
+                let loss_up = loss_of(&shifted, &inputs, loss, Direction::Left);
+                values[parameter_id] -= 2.0 * step;
+                shifted.set_parameters(&values);
+                let loss_down = loss_of(&shifted, &inputs, loss, Direction::Left);
+                let numeric = (loss_up - loss_down) / (2.0 * step);
+                assert!(
+                    (numeric - gradient).abs() < 1e-2,
+                    "{:?} parameter {}: backward {}, finite differences {}",
+                    loss,
+                    parameter_id,
+                    gradient,
+                    numeric
+                );
+            }
+        }
+    }
+    #[test]
+    fn options_choose_the_loss_and_optimizer() {
+        let args: Vec<String> = ["a.snkr", "--loss", "mse", "--optimizer", "sgd", "b.snkr"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let (config, rest) = TrainingConfig::from_args(&args).unwrap();
+        assert!(matches!(config.loss, Loss::MeanSquaredError));
+        assert!(matches!(config.optimizer, OptimizerKind::Sgd { .. }));
+        assert_eq!(rest, vec!["a.snkr", "b.snkr"]);
+        let bad: Vec<String> = vec![String::from("--optimizer"), String::from("rmsprop")];
+        assert!(TrainingConfig::from_args(&bad).is_err());
+    }
+}
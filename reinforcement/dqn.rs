@@ -0,0 +1,231 @@
+use rand::Rng;
+
+use crate::{
+    NETWORK_LAYERS,
+    game::{
+        Direction,
+        env::{RewardConfig, SnakeEnv},
+    },
+    perceptron::{network::Network, training::Optimizer},
+    reinforcement::replay_buffer::{ReplayBuffer, Transition},
+};
+
+// Evaluation episodes use their own seeds so they never repeat a training episode
+const EVALUATION_SEED_OFFSET: u64 = 1_000_000;
+
+pub struct DqnConfig {
+    pub layer_sizes: Vec<usize>,
+    pub episodes: usize,
+    pub gamma: f32,
+    pub learning_rate: f32,
+    pub batch_size: usize,
+    pub buffer_capacity: usize,
+    // Environment steps collected before the first gradient step
+    pub warmup_steps: usize,
+    pub train_interval: usize,
+    pub target_update_interval: usize,
+    pub epsilon_start: f32,
+    pub epsilon_end: f32,
+    pub epsilon_decay_steps: usize,
+    pub evaluation_interval: usize,
+    pub evaluation_episodes: usize,
+    // The output layer is ReLU, so Q values can't go below zero and rewards shouldn't either
+    pub rewards: RewardConfig,
+}
+impl Default for DqnConfig {
+    fn default() -> Self {
+        return Self {
+            layer_sizes: NETWORK_LAYERS.to_vec(),
+            episodes: 5000,
+            gamma: 0.95,
+            learning_rate: 0.0005,
+            batch_size: 64,
+            buffer_capacity: 50_000,
+            warmup_steps: 1000,
+            train_interval: 4,
+            target_update_interval: 1000,
+            epsilon_start: 1.0,
+            epsilon_end: 0.05,
+            epsilon_decay_steps: 100_000,
+            evaluation_interval: 100,
+            evaluation_episodes: 10,
+            rewards: RewardConfig {
+                apple: 1.0,
+                death: 0.0,
+                ..Default::default()
+            },
+        };
+    }
+}
+
+// Deep Q-learning over the same World the GA uses, the Q network is a plain Network
+pub struct DqnTrainer {
+    pub config: DqnConfig,
+    pub q_network: Network,
+    pub target_network: Network,
+    // Network with the best evaluation so far
+    pub best_network: Network,
+    pub best_evaluation: f32,
+    env: SnakeEnv,
+    buffer: ReplayBuffer,
+    optimizer: Optimizer,
+    steps: usize,
+}
+impl DqnTrainer {
+    pub fn new(width: i32, height: i32, config: DqnConfig) -> Self {
+        let q_network = Network::create_random_network(config.layer_sizes.clone());
+        return Self {
+            env: SnakeEnv::new(width, height, config.rewards),
+            buffer: ReplayBuffer::new(config.buffer_capacity),
+            optimizer: Optimizer::adam(config.learning_rate),
+            target_network: q_network.clone(),
+            best_network: q_network.clone(),
+            best_evaluation: f32::NEG_INFINITY,
+            q_network,
+            config,
+            steps: 0,
+        };
+    }
+    pub fn epsilon(&self) -> f32 {
+        let progress = (self.steps as f32 / self.config.epsilon_decay_steps as f32).min(1.0);
+        return self.config.epsilon_start
+            + (self.config.epsilon_end - self.config.epsilon_start) * progress;
+    }
+    pub fn train(&mut self) {
+        let mut rng = rand::rng();
+        for episode in 0..self.config.episodes {
+            let mut observation = self.env.reset(rng.random());
+            let mut done = false;
+            while !done {
+                let action = if rng.random::<f32>() < self.epsilon() {
+                    self.env.action_space().sample(&mut rng)
+                } else {
+                    Direction::from_network(self.q_network.propagate(observation.clone()))
+                };
+                let (next_observation, reward, step_done, _info) = self.env.step(action);
+                done = step_done;
+                self.buffer.push(Transition {
+                    observation,
+                    action,
+                    reward,
+                    next_observation: next_observation.clone(),
+                    done,
+                });
+                observation = next_observation;
+                self.steps += 1;
+
+                // A batch can't be sampled from fewer transitions than it holds
+                let warmed_up =
+                    self.buffer.len() >= self.config.warmup_steps.max(self.config.batch_size);
+                if warmed_up && self.steps.is_multiple_of(self.config.train_interval) {
+                    self.train_batch(&mut rng);
+                }
+                if self
+                    .steps
+                    .is_multiple_of(self.config.target_update_interval)
+                {
+                    self.target_network = self.q_network.clone();
+                }
+            }
+            if (episode + 1).is_multiple_of(self.config.evaluation_interval) {
+                self.keep_if_best(episode + 1);
+            }
+        }
+        // Otherwise the last episodes, or all of them, would never be evaluated
+        if !self
+            .config
+            .episodes
+            .is_multiple_of(self.config.evaluation_interval)
+        {
+            self.keep_if_best(self.config.episodes);
+        }
+    }
+    fn keep_if_best(&mut self, episodes: usize) {
+        let evaluation = self.evaluate();
+        if evaluation > self.best_evaluation {
+            self.best_evaluation = evaluation;
+            self.best_network = self.q_network.clone();
+        }
+        println!(
+            "Episode {}: epsilon {:.3}, evaluation {:.2} apples, best {:.2}",
+            episodes,
+            self.epsilon(),
+            evaluation,
+            self.best_evaluation
+        );
+    }
+    // What the Q value of the taken action should be: r + gamma * max Q_target(s')
+    fn td_target(&self, transition: &Transition) -> f32 {
+        if transition.done {
+            return transition.reward;
+        }
+        let next_q = self
+            .target_network
+            .propagate(transition.next_observation.clone());
+        return transition.reward
+            + self.config.gamma * next_q.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    }
+    // One gradient step of the Q network towards r + gamma * max Q_target(s')
+    fn train_batch(&mut self, rng: &mut impl Rng) {
+        let mut gradients = vec![0.0; self.q_network.parameters_count()];
+        let batch = self.buffer.sample(self.config.batch_size, rng);
+        for transition in &batch {
+            let target = self.td_target(transition);
+            let trace = self.q_network.forward_trace(transition.observation.clone());
+            let action_id = transition.action.index();
+            let mut output_gradients = vec![0.0; trace.output().len()];
+            output_gradients[action_id] =
+                2.0 * (trace.output()[action_id] - target) / batch.len() as f32;
+            self.q_network
+                .backward(&trace, output_gradients, &mut gradients);
+        }
+        let mut parameters = self.q_network.parameters();
+        self.optimizer.step(&mut parameters, &gradients);
+        self.q_network.set_parameters(&parameters);
+    }
+    // Mean apples of the greedy policy on fixed seeds
+    pub fn evaluate(&self) -> f32 {
+        let mut env = SnakeEnv::new(self.env.width, self.env.height, self.config.rewards);
+        let mut apples = 0;
+        for episode in 0..self.config.evaluation_episodes {
+            let mut observation = env.reset(EVALUATION_SEED_OFFSET + episode as u64);
+            let mut done = false;
+            while !done {
+                let action = Direction::from_network(self.q_network.propagate(observation));
+                let step = env.step(action);
+                observation = step.0;
+                done = step.2;
+            }
+            apples += env.world.score;
+        }
+        return apples as f32 / self.config.evaluation_episodes.max(1) as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn td_target_only_looks_ahead_before_the_end() {
+        let config = DqnConfig {
+            layer_sizes: vec![2, 3],
+            gamma: 0.5,
+            ..Default::default()
+        };
+        let trainer = DqnTrainer::new(10, 10, config);
+        let next_observation = vec![0.3, -0.7];
+        let next_q = trainer.target_network.propagate(next_observation.clone());
+        let best_next_q = next_q.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let mut transition = Transition {
+            observation: vec![0.1, 0.2],
+            action: Direction::Left,
+            reward: 1.0,
+            next_observation,
+            done: false,
+        };
+        assert_eq!(trainer.td_target(&transition), 1.0 + 0.5 * best_next_q);
+        transition.done = true;
+        assert_eq!(trainer.td_target(&transition), 1.0);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod dqn;
+pub mod replay_buffer;
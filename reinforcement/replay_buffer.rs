@@ -0,0 +1,83 @@
+use rand::Rng;
+
+use crate::game::Direction;
+
+#[derive(Clone)]
+pub struct Transition {
+    pub observation: Vec<f32>,
+    pub action: Direction,
+    pub reward: f32,
+    pub next_observation: Vec<f32>,
+    pub done: bool,
+}
+
+// Ring buffer of the latest transitions, oldest ones get overwritten when it is full
+pub struct ReplayBuffer {
+    pub capacity: usize,
+    transitions: Vec<Transition>,
+    next_id: usize,
+}
+impl ReplayBuffer {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Replay buffer needs room for a transition");
+        return Self {
+            capacity,
+            transitions: Vec::with_capacity(capacity),
+            next_id: 0,
+        };
+    }
+    pub fn len(&self) -> usize {
+        return self.transitions.len();
+    }
+    pub fn push(&mut self, transition: Transition) {
+        if self.transitions.len() < self.capacity {
+            self.transitions.push(transition);
+        } else {
+            self.transitions[self.next_id] = transition;
+        }
+        self.next_id = (self.next_id + 1) % self.capacity;
+    }
+    // Uniform sample with repetitions
+    pub fn sample(&self, batch_size: usize, rng: &mut impl Rng) -> Vec<&Transition> {
+        let mut batch = vec![];
+        for _sample_id in 0..batch_size {
+            batch.push(&self.transitions[rng.random_range(0..self.transitions.len())]);
+        }
+        return batch;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(reward: f32) -> Transition {
+        return Transition {
+            observation: vec![],
+            action: Direction::Up,
+            reward,
+            next_observation: vec![],
+            done: false,
+        };
+    }
+
+    #[test]
+    fn full_buffer_overwrites_the_oldest_transition() {
+        let mut buffer = ReplayBuffer::new(3);
+        for reward in 0..5 {
+            buffer.push(transition(reward as f32));
+            assert_eq!(buffer.len(), (reward + 1).min(3));
+        }
+        let mut rewards: Vec<f32> = buffer.transitions.iter().map(|t| t.reward).collect();
+        rewards.sort_by(f32::total_cmp);
+        assert_eq!(rewards, vec![2.0, 3.0, 4.0]);
+        let batch = buffer.sample(10, &mut rand::rng());
+        assert_eq!(batch.len(), 10);
+        assert!(batch.iter().all(|t| t.reward >= 2.0));
+    }
+    #[test]
+    #[should_panic]
+    fn empty_capacity_is_rejected() {
+        ReplayBuffer::new(0);
+    }
+}
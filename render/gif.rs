@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use crate::render::raster::{Canvas, PALETTE, palette_rgb};
+
+// Bits per palette index, GIF doesn't go below 2
+const MIN_CODE_SIZE: u32 = 2;
+const MAX_CODE: u16 = 4096;
+const MAX_SUB_BLOCK: usize = 255;
+
+// Animated GIF written frame by frame, so a long episode never has to be kept in memory
+pub struct GifWriter<W: Write> {
+    writer: W,
+    width: usize,
+    height: usize,
+    // Hundredths of a second every frame stays on screen
+    frame_delay: u16,
+}
+impl<W: Write> GifWriter<W> {
+    pub fn new(mut writer: W, width: usize, height: usize, frame_delay: u16) -> io::Result<Self> {
+        writer.write_all(b"GIF89a")?;
+        writer.write_all(&(width as u16).to_le_bytes())?;
+        writer.write_all(&(height as u16).to_le_bytes())?;
+        // Global colour table of 2^(1 + 1) colours, the whole palette, no aspect ratio
+        writer.write_all(&[0b1000_0001, 0, 0])?;
+        for color_id in 0..PALETTE.len() {
+            writer.write_all(&palette_rgb(color_id))?;
+        }
+        // Netscape extension, loops forever
+        writer.write_all(&[0x21, 0xff, 11])?;
+        writer.write_all(b"NETSCAPE2.0")?;
+        writer.write_all(&[3, 1, 0, 0, 0])?;
+        return Ok(Self {
+            writer,
+            width,
+            height,
+            frame_delay,
+        });
+    }
+    pub fn write_frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        if canvas.width != self.width || canvas.height != self.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "all frames of a GIF need the same size",
+            ));
+        }
+        // Graphic control extension with the delay
+        self.writer.write_all(&[0x21, 0xf9, 4, 0])?;
+        self.writer.write_all(&self.frame_delay.to_le_bytes())?;
+        self.writer.write_all(&[0, 0])?;
+        // Image descriptor covering the whole screen, using the global colour table
+        self.writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.writer.write_all(&(self.width as u16).to_le_bytes())?;
+        self.writer.write_all(&(self.height as u16).to_le_bytes())?;
+        self.writer.write_all(&[0, MIN_CODE_SIZE as u8])?;
+        for sub_block in lzw_compress(&canvas.pixels).chunks(MAX_SUB_BLOCK) {
+            self.writer.write_all(&[sub_block.len() as u8])?;
+            self.writer.write_all(sub_block)?;
+        }
+        self.writer.write_all(&[0])?;
+        return Ok(());
+    }
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.write_all(&[0x3b])?;
+        return self.writer.flush();
+    }
+}
+
+fn lzw_compress(pixels: &[u8]) -> Vec<u8> {
+    let clear_code = 1u16 << MIN_CODE_SIZE;
+    let end_code = clear_code + 1;
+    let mut codes = HashMap::new();
+    let mut next_code = end_code + 1;
+    let mut code_size = MIN_CODE_SIZE + 1;
+    let mut bits = BitWriter::default();
+    bits.write(clear_code, code_size);
+
+    let Some((first, rest)) = pixels.split_first() else {
+        bits.write(end_code, code_size);
+        return bits.finish();
+    };
+    let mut prefix = *first as u16;
+    for pixel in rest {
+        if let Some(code) = codes.get(&(prefix, *pixel)) {
+            prefix = *code;
+            continue;
+        }
+        bits.write(prefix, code_size);
+        if next_code < MAX_CODE {
+            codes.insert((prefix, *pixel), next_code);
+            if next_code == 1 << code_size {
+                code_size += 1;
+            }
+            next_code += 1;
+        } else {
+            // Table is full, start over
+            bits.write(clear_code, code_size);
+            codes.clear();
+            next_code = end_code + 1;
+            code_size = MIN_CODE_SIZE + 1;
+        }
+        prefix = *pixel as u16;
+    }
+    bits.write(prefix, code_size);
+    bits.write(end_code, code_size);
+    return bits.finish();
+}
+
+// Codes are packed least significant bit first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    filled: u32,
+}
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.buffer |= (code as u32) << self.filled;
+        self.filled += size;
+        while self.filled >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.filled -= 8;
+        }
+    }
+    fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        return self.bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plain GIF decoder, a code longer than the table is the previous string and its first byte
+    fn lzw_decompress(bytes: &[u8]) -> Vec<u8> {
+        let clear_code = 1usize << MIN_CODE_SIZE;
+        let end_code = clear_code + 1;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let mut code_size = MIN_CODE_SIZE + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        let mut pixels = vec![];
+        let mut bit = 0;
+        loop {
+            let mut code = 0;
+            for bit_id in 0..code_size as usize {
+                let byte = bytes[(bit + bit_id) / 8];
+                code |= (((byte >> ((bit + bit_id) % 8)) & 1) as usize) << bit_id;
+            }
+            bit += code_size as usize;
+            if code == clear_code {
+                table = (0..=end_code).map(|code| vec![code as u8]).collect();
+                code_size = MIN_CODE_SIZE + 1;
+                previous = None;
+                continue;
+            }
+            if code == end_code {
+                return pixels;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) if code == table.len() => {
+                    [previous.clone(), vec![previous[0]]].concat()
+                }
+                _ => panic!("Code {} is not in the table", code),
+            };
+            pixels.extend(&entry);
+            // A full table stays as it is until the next clear code
+            if let Some(previous) = previous.filter(|_| table.len() < MAX_CODE as usize) {
+                table.push([previous, vec![entry[0]]].concat());
+            }
+            if table.len() == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        assert!(lzw_decompress(&lzw_compress(&[])).is_empty());
+        // Long runs like a board, then noise that fills the code table more than once
+        let mut pixels = vec![0; 500];
+        pixels.extend([2; 37]);
+        let mut state = 12345u32;
+        for _ in 0..40000 {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            pixels.push(((state >> 16) % PALETTE.len() as u32) as u8);
+        }
+        assert_eq!(lzw_decompress(&lzw_compress(&pixels)), pixels);
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{
+    CELL_SIZE, FIELD_SIZE,
+    game::{recording::EpisodeRecording, replay::Replay, world::World},
+    perceptron::network::Network,
+    render::{gif::GifWriter, png::write_png, raster::render_board},
+};
+
+pub mod gif;
+pub mod png;
+pub mod raster;
+
+const DEFAULT_FRAME_DELAY_MS: u64 = 100;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RenderFormat {
+    // One numbered file per tick in the output directory
+    PngSequence,
+    Gif,
+}
+impl RenderFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "png" => Some(RenderFormat::PngSequence),
+            "gif" => Some(RenderFormat::Gif),
+            _ => None,
+        };
+    }
+}
+
+pub enum EpisodeSource {
+    Recording(PathBuf),
+    // A saved network plays a new episode on the given seed
+    Network { path: PathBuf, seed: u64 },
+}
+
+pub struct RenderConfig {
+    pub source: EpisodeSource,
+    pub output: PathBuf,
+    pub format: RenderFormat,
+    pub cell_size: f32,
+    pub frame_delay: Duration,
+}
+impl RenderConfig {
+    // render (--recording PATH | --network PATH [--seed S]) --output PATH [--format png|gif]
+    //        [--cell-size PIXELS] [--delay-ms MS]
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut recording = None;
+        let mut network = None;
+        let mut seed = 0;
+        let mut output = None;
+        let mut format = None;
+        let mut cell_size = CELL_SIZE;
+        let mut frame_delay = Duration::from_millis(DEFAULT_FRAME_DELAY_MS);
+        let mut i = 0;
+        while i < args.len() {
+            let value = args
+                .get(i + 1)
+                .ok_or(format!("Missing value for {}", args[i]))?;
+            match args[i].as_str() {
+                "--recording" => recording = Some(PathBuf::from(value)),
+                "--network" => network = Some(PathBuf::from(value)),
+                "--seed" => seed = value.parse().map_err(|_| "Bad --seed")?,
+                "--output" => output = Some(PathBuf::from(value)),
+                "--format" => {
+                    format = Some(RenderFormat::from_name(value).ok_or("Bad --format")?);
+                }
+                "--cell-size" => {
+                    cell_size = value.parse().map_err(|_| "Bad --cell-size")?;
+                    if cell_size < 1.0 {
+                        return Err(String::from("Cells need at least one pixel"));
+                    }
+                }
+                "--delay-ms" => {
+                    frame_delay =
+                        Duration::from_millis(value.parse().map_err(|_| "Bad --delay-ms")?);
+                }
+                other => return Err(format!("Unknown option {}", other)),
+            }
+            i += 2;
+        }
+        let source = match (recording, network) {
+            (Some(path), None) => EpisodeSource::Recording(path),
+            (None, Some(path)) => EpisodeSource::Network { path, seed },
+            _ => return Err(String::from("Give either --recording or --network")),
+        };
+        let output = output.ok_or("Missing --output")?;
+        // Without --format a .gif output is a GIF, anything else a directory of PNGs
+        let format = format.unwrap_or(
+            if output
+                .extension()
+                .is_some_and(|extension| extension == "gif")
+            {
+                RenderFormat::Gif
+            } else {
+                RenderFormat::PngSequence
+            },
+        );
+        return Ok(Self {
+            source,
+            output,
+            format,
+            cell_size,
+            frame_delay,
+        });
+    }
+}
+
+// Renders every tick of the episode, returns how many frames were written
+pub fn render_episode(config: &RenderConfig) -> io::Result<usize> {
+    let recording = match &config.source {
+        EpisodeSource::Recording(path) => EpisodeRecording::load(path)?,
+        EpisodeSource::Network { path, seed } => {
+            let network = Network::load(path)?;
+            network.check_playable()?;
+            let mut world = World::with_seed(FIELD_SIZE, FIELD_SIZE, network, *seed);
+            world.play_to_end();
+            world.recording
+        }
+    };
+    let mut replay = Replay::new(recording);
+    let frame = |world: &World| {
+        render_board(
+            &world.snake.points,
+            world.apple.position,
+            &world.obstacles,
+            world.width,
+            world.height,
+            config.cell_size,
+        )
+    };
+    let replay_error = |err| io::Error::new(io::ErrorKind::InvalidData, format!("{}", err));
+
+    let mut frames_count = 0;
+    match config.format {
+        RenderFormat::PngSequence => {
+            std::fs::create_dir_all(&config.output)?;
+            loop {
+                let path = config.output.join(format!("frame_{:05}.png", frames_count));
+                write_png(
+                    &frame(&replay.world),
+                    &mut BufWriter::new(File::create(path)?),
+                )?;
+                frames_count += 1;
+                if !replay.tick().map_err(replay_error)? {
+                    break;
+                }
+            }
+        }
+        RenderFormat::Gif => {
+            let first_frame = frame(&replay.world);
+            let mut gif = GifWriter::new(
+                BufWriter::new(File::create(&config.output)?),
+                first_frame.width,
+                first_frame.height,
+                (config.frame_delay.as_millis() / 10).min(u16::MAX as u128) as u16,
+            )?;
+            gif.write_frame(&first_frame)?;
+            frames_count += 1;
+            while replay.tick().map_err(replay_error)? {
+                gif.write_frame(&frame(&replay.world))?;
+                frames_count += 1;
+            }
+            gif.finish()?;
+        }
+    }
+    return Ok(frames_count);
+}
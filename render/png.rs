@@ -0,0 +1,232 @@
+use std::io::{self, Write};
+
+use crate::render::raster::{Canvas, PALETTE, palette_rgb};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+// Deflate can't copy more at once or look further back
+const MAX_MATCH: usize = 258;
+const MAX_DISTANCE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const LENGTH_BASES: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+// 8 bit palette PNG, one byte per pixel straight from the canvas
+pub fn write_png(canvas: &Canvas, writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(&SIGNATURE)?;
+
+    let mut header = vec![];
+    header.extend((canvas.width as u32).to_be_bytes());
+    header.extend((canvas.height as u32).to_be_bytes());
+    // Bit depth 8, palette colours, the only compression and filter methods there are, no interlacing
+    header.extend([8, 3, 0, 0, 0]);
+    write_chunk(writer, b"IHDR", &header)?;
+
+    let palette: Vec<u8> = (0..PALETTE.len()).flat_map(palette_rgb).collect();
+    write_chunk(writer, b"PLTE", &palette)?;
+
+    // Every row starts with its filter type, none of them are filtered
+    let mut rows = Vec::with_capacity((canvas.width + 1) * canvas.height);
+    for row in canvas.pixels.chunks(canvas.width.max(1)) {
+        rows.push(0);
+        rows.extend_from_slice(row);
+    }
+    write_chunk(writer, b"IDAT", &zlib_compress(&rows, canvas.width + 1))?;
+    write_chunk(writer, b"IEND", &[])?;
+    return Ok(());
+}
+
+fn write_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let mut crc = Crc32::default();
+    crc.update(kind);
+    crc.update(data);
+    writer.write_all(&crc.finish().to_be_bytes())?;
+    return Ok(());
+}
+
+struct Crc32 {
+    value: u32,
+}
+impl Default for Crc32 {
+    fn default() -> Self {
+        return Self { value: 0xffffffff };
+    }
+}
+impl Crc32 {
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.value ^= *byte as u32;
+            for _ in 0..8 {
+                let mask = (self.value & 1).wrapping_neg();
+                self.value = (self.value >> 1) ^ (0xedb88320 & mask);
+            }
+        }
+    }
+    fn finish(&self) -> u32 {
+        return self.value ^ 0xffffffff;
+    }
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    return (b << 16) | a;
+}
+
+/*
+ * Boards are long runs of one colour and rows that repeat the row above, so a single fixed
+ * Huffman block that only looks one byte and one row back compresses them well enough.
+ */
+fn zlib_compress(data: &[u8], row_length: usize) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    // Last block, fixed Huffman codes
+    bits.write_bits(1, 1);
+    bits.write_bits(1, 2);
+    let mut position = 0;
+    while position < data.len() {
+        let longest = [1, row_length]
+            .iter()
+            .filter(|distance| **distance <= position && **distance <= MAX_DISTANCE)
+            .map(|distance| (match_length(data, position, *distance), *distance))
+            .max();
+        match longest {
+            Some((length, distance)) if length >= MIN_MATCH => {
+                bits.write_length(length);
+                bits.write_distance(distance);
+                position += length;
+            }
+            _ => {
+                bits.write_literal(data[position] as usize);
+                position += 1;
+            }
+        }
+    }
+    // End of block
+    bits.write_literal(256);
+
+    let mut output = vec![0x78, 0x01];
+    output.extend(bits.finish());
+    output.extend(adler32(data).to_be_bytes());
+    return output;
+}
+fn match_length(data: &[u8], position: usize, distance: usize) -> usize {
+    let mut length = 0;
+    while length < MAX_MATCH
+        && position + length < data.len()
+        && data[position + length] == data[position + length - distance]
+    {
+        length += 1;
+    }
+    return length;
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    filled: u32,
+}
+impl BitWriter {
+    // Plain values go least significant bit first
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.filled;
+        self.filled += count;
+        while self.filled >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.filled -= 8;
+        }
+    }
+    // Huffman codes go most significant bit first
+    fn write_code(&mut self, code: u32, count: u32) {
+        self.write_bits(code.reverse_bits() >> (32 - count), count);
+    }
+    fn write_literal(&mut self, symbol: usize) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+    fn write_length(&mut self, length: usize) {
+        let code = LENGTH_BASES
+            .iter()
+            .rposition(|base| *base <= length)
+            .unwrap();
+        self.write_literal(257 + code);
+        self.write_bits(
+            (length - LENGTH_BASES[code]) as u32,
+            LENGTH_EXTRA_BITS[code],
+        );
+    }
+    fn write_distance(&mut self, distance: usize) {
+        let code = DISTANCE_BASES
+            .iter()
+            .rposition(|base| *base <= distance)
+            .unwrap();
+        self.write_code(code as u32, 5);
+        self.write_bits(
+            (distance - DISTANCE_BASES[code]) as u32,
+            DISTANCE_EXTRA_BITS[code],
+        );
+    }
+    fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        return self.bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_match_the_known_answers() {
+        let mut crc = Crc32::default();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+        assert_eq!(adler32(&[]), 1);
+    }
+    #[test]
+    fn small_canvas_is_encoded_byte_for_byte() {
+        // A snake-coloured block under a repeated row, so both match distances are used
+        let mut canvas = Canvas::new(6, 4);
+        canvas.fill_rect(1.0, 1.0, 4.0, 2.0, 2);
+        canvas.pixels[0] = 3;
+        let mut bytes = vec![];
+        write_png(&canvas, &mut bytes).unwrap();
+        // Chunk CRCs and the IDAT stream have been checked with zlib
+        let expected: &[u8] = &[
+            137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 6, 0, 0, 0, 4,
+            8, 3, 0, 0, 0, 154, 218, 190, 113, 0, 0, 0, 12, 80, 76, 84, 69, 130, 130, 130, 230, 41,
+            56, 252, 250, 0, 0, 227, 48, 115, 175, 248, 191, 0, 0, 0, 18, 73, 68, 65, 84, 120, 1,
+            99, 96, 102, 128, 0, 38, 32, 64, 80, 96, 0, 0, 1, 77, 0, 20, 233, 166, 227, 207, 0, 0,
+            0, 0, 73, 69, 78, 68, 174, 66, 96, 130,
+        ];
+        assert_eq!(bytes, expected);
+    }
+}
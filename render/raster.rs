@@ -0,0 +1,109 @@
+use macroquad::color::{Color, GRAY, GREEN, RED, YELLOW};
+
+use crate::{CELL_SIZE, game::Point};
+
+// Every colour draw_world uses, frames store indices into it
+pub const PALETTE: [Color; 4] = [GRAY, RED, YELLOW, GREEN];
+const BACKGROUND: u8 = 0;
+const WALL: u8 = 1;
+const SNAKE: u8 = 2;
+const APPLE: u8 = 3;
+
+// Palette indexed image drawn without a window
+#[derive(Clone, PartialEq)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        return Self {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        };
+    }
+    // Pixels whose centre is inside the rectangle, the same ones a GPU would fill
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: u8) {
+        let first_column = (x - 0.5).ceil().max(0.0) as usize;
+        let last_column = ((x + width - 0.5).ceil().max(0.0) as usize).min(self.width);
+        let first_row = (y - 0.5).ceil().max(0.0) as usize;
+        let last_row = ((y + height - 0.5).ceil().max(0.0) as usize).min(self.height);
+        for row in first_row..last_row {
+            let row_start = row * self.width;
+            self.pixels[row_start + first_column.min(last_column)..row_start + last_column]
+                .fill(color);
+        }
+    }
+}
+
+pub fn palette_rgb(index: usize) -> [u8; 3] {
+    let color = PALETTE[index];
+    let channel = |value: f32| (value * 255.0).round() as u8;
+    return [channel(color.r), channel(color.g), channel(color.b)];
+}
+
+// Same picture as draw_board on a gray window, the board fills the whole canvas
+pub fn render_board(
+    snake: &[Point],
+    apple: Point,
+    obstacles: &[Point],
+    width: i32,
+    height: i32,
+    cell_size: f32,
+) -> Canvas {
+    let board_width = width as f32 * cell_size;
+    let board_height = height as f32 * cell_size;
+    let mut canvas = Canvas::new(board_width.round() as usize, board_height.round() as usize);
+    // draw_rectangle_lines puts half of the thickness inside the rectangle, one cell
+    canvas.fill_rect(0.0, 0.0, board_width, cell_size, WALL);
+    canvas.fill_rect(0.0, board_height - cell_size, board_width, cell_size, WALL);
+    canvas.fill_rect(0.0, 0.0, cell_size, board_height, WALL);
+    canvas.fill_rect(board_width - cell_size, 0.0, cell_size, board_height, WALL);
+
+    let gap = cell_size / CELL_SIZE;
+    let mut fill_cell = |point: Point, color: u8| {
+        canvas.fill_rect(
+            point.x as f32 * cell_size,
+            point.y as f32 * cell_size,
+            cell_size - gap,
+            cell_size - gap,
+            color,
+        );
+    };
+    // Obstacles are walls inside the board
+    for obstacle in obstacles {
+        fill_cell(*obstacle, WALL);
+    }
+    for snake_point in snake {
+        fill_cell(*snake_point, SNAKE);
+    }
+    fill_cell(apple, APPLE);
+    return canvas;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obstacles_are_drawn_like_walls() {
+        let obstacle = Point { x: 3, y: 2 };
+        let canvas = render_board(
+            &[Point { x: 5, y: 5 }],
+            Point { x: 1, y: 1 },
+            &[obstacle],
+            8,
+            8,
+            1.0,
+        );
+        let pixel =
+            |point: Point| canvas.pixels[point.y as usize * canvas.width + point.x as usize];
+        assert_eq!(pixel(obstacle), WALL);
+        assert_eq!(pixel(Point { x: 0, y: 4 }), WALL);
+        assert_eq!(pixel(Point { x: 4, y: 2 }), BACKGROUND);
+        assert_eq!(pixel(Point { x: 5, y: 5 }), SNAKE);
+        assert_eq!(pixel(Point { x: 1, y: 1 }), APPLE);
+    }
+}
@@ -0,0 +1,430 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    iter::Peekable,
+    os::unix::{fs::FileTypeExt, net::UnixListener},
+    str::Chars,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    FIELD_SIZE,
+    game::{
+        Direction, Point,
+        env::{RewardConfig, SnakeEnv},
+        world::{StepOutcome, World},
+    },
+};
+
+const DEFAULT_EPISODES: usize = 1;
+const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+/*
+ * Line-delimited JSON protocol for agents living outside of this program.
+ * First the server describes what it is going to send and take
+ *   {"type":"spaces","observation":{"size":27,"low":[...],"high":[...]},"actions":4}
+ * then every turn it sends
+ *   {"type":"observation","episode":0,"tick":0,"inputs":[...World::get_inputs],"reward":0.0,
+ *    "score":0,"board":{"width":40,"height":40,"snake":[[x,y],...],"apple":[x,y],
+ *    "obstacles":[[x,y],...],"dir":[x,y]}}
+ * and expects one line back: {"action":"Up","tick":0}, "Up", Up or the output neuron index 0..3.
+ * Echoing the tick is optional, with it answers that come after their tick has timed out are
+ * told apart and dropped. If no answer comes in time the previous action is repeated, answers
+ * still queued when the next observation goes out are dropped.
+ * After every episode {"type":"summary",...} is sent, after the last one {"type":"done"}.
+ */
+pub struct ServerConfig {
+    pub episodes: usize,
+    pub seed: u64,
+    pub move_timeout: Duration,
+    pub socket: Option<String>,
+}
+impl ServerConfig {
+    // serve [--episodes N] [--seed S] [--timeout-ms MS] [--socket PATH]
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = Self {
+            episodes: DEFAULT_EPISODES,
+            seed: 0,
+            move_timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+            socket: None,
+        };
+        let mut i = 0;
+        while i < args.len() {
+            let value = args
+                .get(i + 1)
+                .ok_or(format!("Missing value for {}", args[i]))?;
+            match args[i].as_str() {
+                "--episodes" => config.episodes = value.parse().map_err(|_| "Bad --episodes")?,
+                "--seed" => config.seed = value.parse().map_err(|_| "Bad --seed")?,
+                "--timeout-ms" => {
+                    let millis = value.parse().map_err(|_| "Bad --timeout-ms")?;
+                    config.move_timeout = Duration::from_millis(millis);
+                }
+                "--socket" => config.socket = Some(value.clone()),
+                other => return Err(format!("Unknown option {}", other)),
+            }
+            i += 2;
+        }
+        return Ok(config);
+    }
+}
+
+pub fn serve(config: ServerConfig) -> io::Result<()> {
+    return match &config.socket {
+        Some(path) => {
+            remove_stale_socket(path)?;
+            let listener = UnixListener::bind(path)?;
+            eprintln!("Waiting for an agent on {}", path);
+            let result = listener.accept().and_then(|(stream, _)| {
+                let reader = stream.try_clone()?;
+                run_session(&config, reader, stream)
+            });
+            let _ = std::fs::remove_file(path);
+            result
+        }
+        None => run_session(&config, io::stdin(), io::stdout()),
+    };
+}
+
+// A socket left by an earlier session is taken over, anything else at the path is kept
+fn remove_stale_socket(path: &str) -> io::Result<()> {
+    return match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path),
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    };
+}
+
+fn run_session(
+    config: &ServerConfig,
+    reader: impl Read + Send + 'static,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let actions = spawn_reader(reader);
+    let mut env = SnakeEnv::new(FIELD_SIZE, FIELD_SIZE, RewardConfig::default());
+    writeln!(writer, "{}", spaces_json(&env))?;
+    for episode in 0..config.episodes {
+        let seed = config.seed + episode as u64;
+        env.reset(seed);
+        let mut reward = 0.0;
+        let mut last_action = Direction::Up;
+        let mut timeouts = 0;
+        let mut disconnected = false;
+        let mut outcome = StepOutcome::Moved;
+        while env.world.alive {
+            // Answers that came after their tick had timed out would be taken for this one
+            while actions.try_recv().is_ok() {}
+            writeln!(writer, "{}", observation_json(episode, &env.world, reward))?;
+            writer.flush()?;
+            let tick = env.world.life_time;
+            let deadline = Instant::now() + config.move_timeout;
+            loop {
+                match actions.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(line) => match parse_reply(&line) {
+                        // Late for an earlier tick, the answer to this one may still come
+                        Some(reply) if reply.tick.is_some_and(|reply_tick| reply_tick != tick) => {}
+                        Some(reply) => {
+                            last_action = reply.action;
+                            break;
+                        }
+                        None => {
+                            eprintln!("Could not parse action: {}", line.trim());
+                            break;
+                        }
+                    },
+                    Err(RecvTimeoutError::Timeout) => {
+                        timeouts += 1;
+                        break;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        disconnected = true;
+                        break;
+                    }
+                }
+            }
+            if disconnected {
+                break;
+            }
+            let step = env.step(last_action);
+            reward = step.1;
+            if let Some(step_outcome) = step.3.outcome {
+                outcome = step_outcome;
+            }
+        }
+        writeln!(
+            writer,
+            "{}",
+            summary_json(episode, seed, &env.world, outcome, timeouts)
+        )?;
+        if disconnected {
+            eprintln!("Agent has disconnected");
+            return writer.flush();
+        }
+    }
+    writeln!(writer, "{{\"type\":\"done\"}}")?;
+    return writer.flush();
+}
+
+// Agent answers are read on their own thread so a slow agent can be timed out
+fn spawn_reader(reader: impl Read + Send + 'static) -> Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+            if line.trim().is_empty() {
+                continue;
+            }
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    return rx;
+}
+
+// One answer of the agent, tick is only known when the agent echoes it back
+#[derive(PartialEq, Debug)]
+pub struct AgentReply {
+    pub action: Direction,
+    pub tick: Option<usize>,
+}
+
+pub fn parse_reply(line: &str) -> Option<AgentReply> {
+    let line = line.trim();
+    if !line.starts_with('{') {
+        return Some(AgentReply {
+            action: parse_direction(line.trim_matches('"'))?,
+            tick: None,
+        });
+    }
+    let mut action = None;
+    let mut tick = None;
+    for (key, value) in parse_object(line)? {
+        match key.as_str() {
+            "action" => action = Some(parse_direction(&value)?),
+            "tick" => tick = Some(value.parse().ok()?),
+            _ => {}
+        }
+    }
+    return Some(AgentReply {
+        action: action?,
+        tick,
+    });
+}
+fn parse_direction(value: &str) -> Option<Direction> {
+    return match value.trim().to_lowercase().as_str() {
+        "up" | "0" => Some(Direction::Up),
+        "down" | "1" => Some(Direction::Down),
+        "left" | "2" => Some(Direction::Left),
+        "right" | "3" => Some(Direction::Right),
+        _ => None,
+    };
+}
+
+type JsonChars<'a> = Peekable<Chars<'a>>;
+
+// Keys and values of a JSON object, strings unquoted, anything nested kept as its text
+fn parse_object(text: &str) -> Option<Vec<(String, String)>> {
+    let mut chars = text.chars().peekable();
+    let mut fields = vec![];
+    skip_whitespace(&mut chars);
+    if chars.next()? != '{' {
+        return None;
+    }
+    skip_whitespace(&mut chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+    } else {
+        loop {
+            skip_whitespace(&mut chars);
+            if chars.next()? != '"' {
+                return None;
+            }
+            let key = read_string(&mut chars)?;
+            skip_whitespace(&mut chars);
+            if chars.next()? != ':' {
+                return None;
+            }
+            skip_whitespace(&mut chars);
+            fields.push((key, read_value(&mut chars)?));
+            skip_whitespace(&mut chars);
+            match chars.next()? {
+                ',' => continue,
+                '}' => break,
+                _ => return None,
+            }
+        }
+    }
+    skip_whitespace(&mut chars);
+    if chars.next().is_some() {
+        return None;
+    }
+    return Some(fields);
+}
+fn skip_whitespace(chars: &mut JsonChars) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+// The opening quote has been read already
+fn read_string(chars: &mut JsonChars) -> Option<String> {
+    let mut string = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(string),
+            '\\' => match chars.next()? {
+                'n' => string.push('\n'),
+                't' => string.push('\t'),
+                'r' => string.push('\r'),
+                'b' => string.push('\u{8}'),
+                'f' => string.push('\u{c}'),
+                'u' => {
+                    let code: String = (0..4).map(|_| chars.next()).collect::<Option<_>>()?;
+                    string.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+                }
+                other => string.push(other),
+            },
+            c => string.push(c),
+        }
+    }
+}
+fn read_value(chars: &mut JsonChars) -> Option<String> {
+    if chars.peek() == Some(&'"') {
+        chars.next();
+        return read_string(chars);
+    }
+    let mut value = String::new();
+    let mut depth = 0;
+    while let Some(&c) = chars.peek() {
+        if depth == 0 && (c == ',' || c == '}' || c.is_whitespace()) {
+            break;
+        }
+        chars.next();
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            // Brackets inside nested strings don't count
+            '"' => {
+                value += &format!("\"{}\"", read_string(chars)?);
+                continue;
+            }
+            _ => {}
+        }
+        value.push(c);
+    }
+    if value.is_empty() || depth != 0 {
+        return None;
+    }
+    return Some(value);
+}
+
+fn spaces_json(env: &SnakeEnv) -> String {
+    let space = env.observation_space();
+    let low: Vec<String> = space.low.iter().map(|v| json_f32(*v)).collect();
+    let high: Vec<String> = space.high.iter().map(|v| json_f32(*v)).collect();
+    return format!(
+        "{{\"type\":\"spaces\",\"observation\":{{\"size\":{},\"low\":[{}],\"high\":[{}]}},\"actions\":{}}}",
+        space.size,
+        low.join(","),
+        high.join(","),
+        env.action_space().n
+    );
+}
+fn observation_json(episode: usize, world: &World, reward: f32) -> String {
+    let inputs: Vec<String> = world.get_inputs().iter().map(|v| json_f32(*v)).collect();
+    return format!(
+        "{{\"type\":\"observation\",\"episode\":{},\"tick\":{},\"inputs\":[{}],\"reward\":{},\"score\":{},\"board\":{{\"width\":{},\"height\":{},\"snake\":{},\"apple\":{},\"obstacles\":{},\"dir\":{}}}}}",
+        episode,
+        world.life_time,
+        inputs.join(","),
+        json_f32(reward),
+        world.score,
+        world.width,
+        world.height,
+        json_points(&world.snake.points),
+        json_point(world.apple.position),
+        json_points(&world.obstacles),
+        json_point(world.snake.dir),
+    );
+}
+
+fn summary_json(
+    episode: usize,
+    seed: u64,
+    world: &World,
+    outcome: StepOutcome,
+    timeouts: usize,
+) -> String {
+    let death_cause = match outcome {
+        StepOutcome::Died(cause) => format!("\"{}\"", cause.name()),
+        _ => String::from("null"),
+    };
+    return format!(
+        "{{\"type\":\"summary\",\"episode\":{},\"seed\":{},\"score\":{},\"life_time\":{},\"death_cause\":{},\"timeouts\":{}}}",
+        episode, seed, world.score, world.life_time, death_cause, timeouts
+    );
+}
+
+// Inputs and rewards are always finite, a NaN would still be sent as null to keep the JSON valid
+fn json_f32(value: f32) -> String {
+    if value.is_finite() {
+        return format!("{}", value);
+    }
+    return String::from("null");
+}
+fn json_point(point: Point) -> String {
+    return format!("[{},{}]", point.x, point.y);
+}
+fn json_points(points: &Vec<Point>) -> String {
+    let points: Vec<String> = points.iter().map(|point| json_point(*point)).collect();
+    return format!("[{}]", points.join(","));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(action: Direction, tick: Option<usize>) -> Option<AgentReply> {
+        return Some(AgentReply { action, tick });
+    }
+
+    #[test]
+    fn parses_plain_answers() {
+        assert_eq!(parse_reply("Up"), reply(Direction::Up, None));
+        assert_eq!(parse_reply(" \"left\" "), reply(Direction::Left, None));
+        assert_eq!(parse_reply("3"), reply(Direction::Right, None));
+        assert_eq!(parse_reply("sideways"), None);
+    }
+    #[test]
+    fn parses_objects_in_any_field_order() {
+        assert_eq!(
+            parse_reply("{\"action\":\"Up\",\"tick\":3}"),
+            reply(Direction::Up, Some(3))
+        );
+        assert_eq!(
+            parse_reply("{ \"tick\" : 7 , \"action\" : 1 }"),
+            reply(Direction::Down, Some(7))
+        );
+    }
+    #[test]
+    fn ignores_unknown_fields() {
+        assert_eq!(
+            parse_reply(
+                "{\"debug\":{\"q\":[1,2],\"note\":\"a } in a string\"},\"action\":\"Left\",\"ok\":true}"
+            ),
+            reply(Direction::Left, None)
+        );
+    }
+    #[test]
+    fn rejects_broken_objects() {
+        assert_eq!(parse_reply("{\"action\":\"Up\""), None);
+        assert_eq!(parse_reply("{\"tick\":3}"), None);
+        assert_eq!(parse_reply("{\"action\":\"Up\",\"tick\":\"soon\"}"), None);
+        assert_eq!(parse_reply("{\"action\":\"Up\"} trailing"), None);
+    }
+}
@@ -0,0 +1,583 @@
+use std::{fs, io, path::Path};
+
+use rand::Rng;
+
+use crate::{
+    game::world::World,
+    perceptron::network::Network,
+    strategies::{SearchStrategy, gaussian},
+};
+
+// Numbers from Hansen's "The CMA Evolution Strategy: A Tutorial"
+const TOL_X: f64 = 1e-11;
+const MAX_CONDITION: f64 = 1e14;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RestartStrategy {
+    None,
+    // Every restart doubles the population
+    Ipop,
+    // Alternates between IPOP-like large populations and small ones with a small step size
+    Bipop,
+}
+impl RestartStrategy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "none" => Some(RestartStrategy::None),
+            "ipop" => Some(RestartStrategy::Ipop),
+            "bipop" => Some(RestartStrategy::Bipop),
+            _ => None,
+        };
+    }
+}
+
+pub struct CmaesConfig {
+    pub sigma: f64,
+    // None means the default 4 + 3 ln(n)
+    pub population: Option<usize>,
+    pub restarts: RestartStrategy,
+    // Restart when the best fitness hasn't improved for this many generations
+    pub stagnation_generations: usize,
+}
+impl Default for CmaesConfig {
+    fn default() -> Self {
+        return Self {
+            sigma: 0.5,
+            population: None,
+            restarts: RestartStrategy::Bipop,
+            stagnation_generations: 50,
+        };
+    }
+}
+
+// State of a single CMA-ES run, restarts replace it with a new one
+struct CmaesRun {
+    lambda: usize,
+    mu: usize,
+    weights: Vec<f64>,
+    mueff: f64,
+    cc: f64,
+    cs: f64,
+    c1: f64,
+    cmu: f64,
+    damps: f64,
+    chi_n: f64,
+    mean: Vec<f64>,
+    sigma: f64,
+    // Row-major n x n matrices
+    covariance: Vec<f64>,
+    eigenvectors: Vec<f64>,
+    eigenvalues_sqrt: Vec<f64>,
+    path_c: Vec<f64>,
+    path_s: Vec<f64>,
+    evaluations: usize,
+    generations: usize,
+    // Generation of the last decomposition and how many generations it is kept for
+    eigen_generation: usize,
+    eigen_interval: usize,
+    best_fitness: usize,
+    generations_without_improvement: usize,
+}
+impl CmaesRun {
+    fn new(mean: Vec<f64>, sigma: f64, lambda: usize) -> Self {
+        let n = mean.len();
+        let nf = n as f64;
+        let mu = lambda / 2;
+        let mut weights: Vec<f64> = (0..mu)
+            .map(|i| ((lambda as f64 + 1.0) / 2.0).ln() - ((i + 1) as f64).ln())
+            .collect();
+        let weights_sum: f64 = weights.iter().sum();
+        weights.iter_mut().for_each(|weight| *weight /= weights_sum);
+        let mueff = 1.0 / weights.iter().map(|weight| weight * weight).sum::<f64>();
+
+        let cc = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+        let cs = (mueff + 2.0) / (nf + mueff + 5.0);
+        let c1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (0.0_f64).max(((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0) + cs;
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+        // The covariance barely moves in one generation, so B and D are only refreshed every
+        // 1 / ((c1 + cmu) n 10) generations, about n / (10 lambda)
+        let eigen_interval = ((1.0 / ((c1 + cmu) * nf * 10.0)).floor() as usize).max(1);
+
+        return Self {
+            lambda,
+            mu,
+            weights,
+            mueff,
+            cc,
+            cs,
+            c1,
+            cmu,
+            damps,
+            chi_n,
+            mean,
+            sigma,
+            covariance: identity(n),
+            eigenvectors: identity(n),
+            eigenvalues_sqrt: vec![1.0; n],
+            path_c: vec![0.0; n],
+            path_s: vec![0.0; n],
+            evaluations: 0,
+            generations: 0,
+            eigen_generation: 0,
+            eigen_interval,
+            best_fitness: 0,
+            generations_without_improvement: 0,
+        };
+    }
+    // Steps of the samples from the mean, in units of sigma
+    fn sample(&self, rng: &mut impl Rng) -> Vec<Vec<f64>> {
+        let n = self.mean.len();
+        let mut steps = vec![];
+        for _sample_id in 0..self.lambda {
+            let z: Vec<f64> = (0..n)
+                .map(|i| gaussian(rng) as f64 * self.eigenvalues_sqrt[i])
+                .collect();
+            steps.push(mat_vec(&self.eigenvectors, &z, n));
+        }
+        return steps;
+    }
+    fn update(&mut self, steps: &Vec<Vec<f64>>, fitness: &Vec<usize>) {
+        let n = self.mean.len();
+        self.evaluations += steps.len();
+        self.generations += 1;
+        let mut order: Vec<usize> = (0..steps.len()).collect();
+        // Best first
+        order.sort_by_key(|id| std::cmp::Reverse(fitness[*id]));
+
+        let best = fitness[order[0]];
+        if best > self.best_fitness {
+            self.best_fitness = best;
+            self.generations_without_improvement = 0;
+        } else {
+            self.generations_without_improvement += 1;
+        }
+
+        let mut step_w = vec![0.0; n];
+        for i in 0..self.mu {
+            for j in 0..n {
+                step_w[j] += self.weights[i] * steps[order[i]][j];
+            }
+        }
+        for (mean, step) in self.mean.iter_mut().zip(&step_w) {
+            *mean += self.sigma * step;
+        }
+
+        // C^-1/2 * step_w = B * D^-1 * B^T * step_w
+        let mut rotated = mat_t_vec(&self.eigenvectors, &step_w, n);
+        for (value, eigenvalue_sqrt) in rotated.iter_mut().zip(&self.eigenvalues_sqrt) {
+            *value /= eigenvalue_sqrt;
+        }
+        let whitened = mat_vec(&self.eigenvectors, &rotated, n);
+        let cs_norm = (self.cs * (2.0 - self.cs) * self.mueff).sqrt();
+        for (path, value) in self.path_s.iter_mut().zip(&whitened) {
+            *path = (1.0 - self.cs) * *path + cs_norm * value;
+        }
+        let path_s_norm = norm(&self.path_s);
+        let generations = self.generations as i32;
+        let hsig = path_s_norm / (1.0 - (1.0 - self.cs).powi(2 * generations)).sqrt() / self.chi_n
+            < 1.4 + 2.0 / (n as f64 + 1.0);
+        let hsig = if hsig { 1.0 } else { 0.0 };
+        let cc_norm = (self.cc * (2.0 - self.cc) * self.mueff).sqrt();
+        for (path, step) in self.path_c.iter_mut().zip(&step_w) {
+            *path = (1.0 - self.cc) * *path + hsig * cc_norm * step;
+        }
+
+        let old_weight =
+            1.0 - self.c1 - self.cmu + (1.0 - hsig) * self.c1 * self.cc * (2.0 - self.cc);
+        for row in 0..n {
+            for col in 0..=row {
+                let mut value = old_weight * self.covariance[row * n + col]
+                    + self.c1 * self.path_c[row] * self.path_c[col];
+                for i in 0..self.mu {
+                    let step = &steps[order[i]];
+                    value += self.cmu * self.weights[i] * step[row] * step[col];
+                }
+                self.covariance[row * n + col] = value;
+                self.covariance[col * n + row] = value;
+            }
+        }
+
+        self.sigma *= ((self.cs / self.damps) * (path_s_norm / self.chi_n - 1.0)).exp();
+
+        // Decomposition is O(n^3), so it is only refreshed once in a while
+        if self.generations - self.eigen_generation >= self.eigen_interval {
+            self.eigen_generation = self.generations;
+            let (values, vectors) = jacobi_eigen(&self.covariance, n);
+            self.eigenvalues_sqrt = values.iter().map(|value| value.max(1e-20).sqrt()).collect();
+            self.eigenvectors = vectors;
+        }
+    }
+    fn should_restart(&self, stagnation_generations: usize) -> bool {
+        let max_d = self.eigenvalues_sqrt.iter().cloned().fold(0.0, f64::max);
+        let min_d = self
+            .eigenvalues_sqrt
+            .iter()
+            .cloned()
+            .fold(f64::INFINITY, f64::min);
+        return self.generations_without_improvement >= stagnation_generations
+            || self.sigma * max_d < TOL_X
+            || (max_d / min_d).powi(2) > MAX_CONDITION
+            || !self.sigma.is_finite();
+    }
+}
+
+// CMA-ES over the flattened network parameters, with IPOP / BIPOP restarts
+pub struct CmaesStrategy {
+    pub config: CmaesConfig,
+    pub restarts: usize,
+    template: Network,
+    default_lambda: usize,
+    run: CmaesRun,
+    steps: Vec<Vec<f64>>,
+    // BIPOP budgets spent by each regime and the large population restarts count
+    large_budget: usize,
+    small_budget: usize,
+    large_restarts: u32,
+    // Whether the current run is a small population one, the first run is not
+    small_run: bool,
+}
+impl CmaesStrategy {
+    pub fn new(layer_sizes: Vec<usize>, config: CmaesConfig) -> Self {
+        let template = Network::create_random_network(layer_sizes);
+        let n = template.parameters_count();
+        let default_lambda = config
+            .population
+            .unwrap_or(4 + (3.0 * (n as f64).ln()).floor() as usize);
+        let mean = to_f64(&template.parameters());
+        return Self {
+            run: CmaesRun::new(mean, config.sigma, default_lambda),
+            config,
+            restarts: 0,
+            template,
+            default_lambda,
+            steps: vec![],
+            large_budget: 0,
+            small_budget: 0,
+            large_restarts: 0,
+            small_run: false,
+        };
+    }
+    fn restart(&mut self) {
+        let mut rng = rand::rng();
+        let budget = self.run.evaluations;
+        let mean: Vec<f64> = (0..self.run.mean.len())
+            .map(|_| rng.random_range(-1.0..=1.0))
+            .collect();
+        let (lambda, sigma) = match self.config.restarts {
+            RestartStrategy::None => (self.run.lambda, self.config.sigma),
+            RestartStrategy::Ipop => (self.run.lambda * 2, self.config.sigma),
+            RestartStrategy::Bipop => {
+                if self.small_run {
+                    self.small_budget += budget;
+                } else {
+                    self.large_budget += budget;
+                }
+                self.small_run = self.small_budget < self.large_budget;
+                if self.small_run {
+                    let u: f64 = rng.random();
+                    let ratio = self.large_lambda() as f64 / 2.0 / self.default_lambda as f64;
+                    let lambda = (self.default_lambda as f64 * ratio.powf(u * u)).floor() as usize;
+                    (
+                        lambda.max(self.default_lambda),
+                        self.config.sigma * 10.0_f64.powf(-2.0 * u),
+                    )
+                } else {
+                    self.large_restarts += 1;
+                    (self.large_lambda(), self.config.sigma)
+                }
+            }
+        };
+        self.restarts += 1;
+        println!(
+            "CMA-ES restart {}: population {}, sigma {:.4}",
+            self.restarts, lambda, sigma
+        );
+        self.run = CmaesRun::new(mean, sigma, lambda);
+    }
+    fn large_lambda(&self) -> usize {
+        return self.default_lambda * 2_usize.pow(self.large_restarts);
+    }
+}
+impl SearchStrategy for CmaesStrategy {
+    fn name(&self) -> &'static str {
+        return "CMA-ES";
+    }
+    fn ask(&mut self) -> Vec<Network> {
+        let mut rng = rand::rng();
+        self.steps = self.run.sample(&mut rng);
+        let mut networks = vec![];
+        for step in &self.steps {
+            let parameters: Vec<f32> = self
+                .run
+                .mean
+                .iter()
+                .zip(step)
+                .map(|(mean, step)| (mean + self.run.sigma * step) as f32)
+                .collect();
+            let mut network = self.template.clone();
+            network.set_parameters(&parameters);
+            networks.push(network);
+        }
+        return networks;
+    }
+    fn tell(&mut self, worlds: Vec<World>) {
+        assert_eq!(
+            worlds.len(),
+            self.steps.len(),
+            "CMA-ES has to be told about every network it has asked for"
+        );
+        let fitness: Vec<usize> = worlds.iter().map(|world| world.fitness_score).collect();
+        self.run.update(&self.steps, &fitness);
+        if self.config.restarts != RestartStrategy::None
+            && self.run.should_restart(self.config.stagnation_generations)
+        {
+            self.restart();
+        }
+    }
+    // Only the mean is saved, as a network, the covariance is too big to be worth it
+    fn save_checkpoint(&self, path: &Path) -> io::Result<()> {
+        let mut mean = self.template.clone();
+        mean.set_parameters(&to_f32(&self.run.mean));
+        return fs::write(path, mean.to_text());
+    }
+}
+
+fn to_f64(values: &Vec<f32>) -> Vec<f64> {
+    return values.iter().map(|value| *value as f64).collect();
+}
+fn to_f32(values: &Vec<f64>) -> Vec<f32> {
+    return values.iter().map(|value| *value as f32).collect();
+}
+fn identity(n: usize) -> Vec<f64> {
+    let mut matrix = vec![0.0; n * n];
+    for i in 0..n {
+        matrix[i * n + i] = 1.0;
+    }
+    return matrix;
+}
+fn norm(vector: &Vec<f64>) -> f64 {
+    return vector.iter().map(|value| value * value).sum::<f64>().sqrt();
+}
+fn mat_vec(matrix: &Vec<f64>, vector: &Vec<f64>, n: usize) -> Vec<f64> {
+    return (0..n)
+        .map(|row| (0..n).map(|col| matrix[row * n + col] * vector[col]).sum())
+        .collect();
+}
+fn mat_t_vec(matrix: &Vec<f64>, vector: &Vec<f64>, n: usize) -> Vec<f64> {
+    let mut result = vec![0.0; n];
+    for row in 0..n {
+        for col in 0..n {
+            result[col] += matrix[row * n + col] * vector[row];
+        }
+    }
+    return result;
+}
+// Cyclic Jacobi rotations, returns eigenvalues and eigenvectors as columns of a row-major matrix
+fn jacobi_eigen(matrix: &Vec<f64>, n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut a = matrix.clone();
+    let mut v = identity(n);
+    for _sweep in 0..50 {
+        let mut off_diagonal = 0.0;
+        for row in 0..n {
+            for col in (row + 1)..n {
+                off_diagonal += a[row * n + col] * a[row * n + col];
+            }
+        }
+        if off_diagonal < 1e-22 {
+            break;
+        }
+        for p in 0..n {
+            for q in (p + 1)..n {
+                let apq = a[p * n + q];
+                if apq.abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q * n + q] - a[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let akp = a[k * n + p];
+                    let akq = a[k * n + q];
+                    a[k * n + p] = c * akp - s * akq;
+                    a[k * n + q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let apk = a[p * n + k];
+                    let aqk = a[q * n + k];
+                    a[p * n + k] = c * apk - s * aqk;
+                    a[q * n + k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let vkp = v[k * n + p];
+                    let vkq = v[k * n + q];
+                    v[k * n + p] = c * vkp - s * vkq;
+                    v[k * n + q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    let values = (0..n).map(|i| a[i * n + i]).collect();
+    return (values, v);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+    // Higher is better, so the distance from the optimum is turned around
+    fn quadratic_fitness(point: &[f64], scales: &[f64]) -> usize {
+        let value: f64 = point
+            .iter()
+            .zip(scales)
+            .map(|(x, scale)| scale * (x - 1.0) * (x - 1.0))
+            .sum();
+        return (1e15 / (1.0 + 1e6 * value)) as usize;
+    }
+    fn run_on_quadratic(run: &mut CmaesRun, scales: &[f64], generations: usize) {
+        let mut rng = rand::rng();
+        for _generation in 0..generations {
+            let steps = run.sample(&mut rng);
+            let fitness = steps
+                .iter()
+                .map(|step| {
+                    let point: Vec<f64> = run
+                        .mean
+                        .iter()
+                        .zip(step)
+                        .map(|(mean, step)| mean + run.sigma * step)
+                        .collect();
+                    quadratic_fitness(&point, scales)
+                })
+                .collect();
+            run.update(&steps, &fitness);
+        }
+    }
+    fn worlds_with_fitness(count: usize, fitness: usize) -> Vec<World> {
+        return (0..count)
+            .map(|_| {
+                let mut world = World::with_seed(10, 10, Network::new_empty(), 0);
+                world.fitness_score = fitness;
+                world
+            })
+            .collect();
+    }
+
+    #[test]
+    fn jacobi_eigen_reconstructs_a_symmetric_matrix() {
+        let n = 4;
+        let matrix = vec![
+            4.0, 1.0, 2.0, 0.5, //
+            1.0, 3.0, 0.0, -1.0, //
+            2.0, 0.0, 5.0, 0.3, //
+            0.5, -1.0, 0.3, 2.0,
+        ];
+        let (values, vectors) = jacobi_eigen(&matrix, n);
+        for row in 0..n {
+            for col in 0..n {
+                // B D B^T gives the matrix back
+                let reconstructed: f64 = (0..n)
+                    .map(|k| vectors[row * n + k] * values[k] * vectors[col * n + k])
+                    .sum();
+                assert_close(reconstructed, matrix[row * n + col]);
+                // B^T B is the identity
+                let dot: f64 = (0..n)
+                    .map(|k| vectors[k * n + row] * vectors[k * n + col])
+                    .sum();
+                assert_close(dot, if row == col { 1.0 } else { 0.0 });
+            }
+        }
+        assert_close(values.iter().sum(), 14.0);
+    }
+    #[test]
+    fn converges_on_a_quadratic_and_learns_its_shape() {
+        let scales = [1.0, 100.0, 1.0, 1.0];
+        let mut run = CmaesRun::new(vec![0.0; 4], 0.5, 8);
+        run_on_quadratic(&mut run, &scales, 300);
+        for (mean, scale) in run.mean.iter().zip(&scales) {
+            assert!((mean - 1.0).abs() * scale.sqrt() < 1e-3, "{:?}", run.mean);
+        }
+        assert!(run.sigma < 0.05, "sigma {}", run.sigma);
+        // The steep direction gets the small variance
+        let n = 4;
+        assert!(run.covariance[n + 1] * 10.0 < run.covariance[0]);
+        for row in 0..n {
+            for col in 0..n {
+                assert_eq!(run.covariance[row * n + col], run.covariance[col * n + row]);
+            }
+        }
+    }
+    #[test]
+    fn decomposes_only_every_few_generations_in_high_dimensions() {
+        let run = CmaesRun::new(vec![0.0; 1024], 0.5, 24);
+        assert!(
+            run.eigen_interval >= 1024 / (10 * 24),
+            "{}",
+            run.eigen_interval
+        );
+        let mut run = CmaesRun::new(vec![0.0; 150], 0.5, 8);
+        let interval = run.eigen_interval;
+        assert!(interval > 1);
+        run_on_quadratic(&mut run, &[1.0; 150], interval - 1);
+        assert_eq!(run.eigen_generation, 0);
+        run_on_quadratic(&mut run, &[1.0; 150], 1);
+        assert_eq!(run.eigen_generation, interval);
+    }
+    #[test]
+    fn restarts_once_the_run_stagnates_or_collapses() {
+        let mut run = CmaesRun::new(vec![0.0; 4], 0.5, 8);
+        assert!(!run.should_restart(10));
+        run.generations_without_improvement = 10;
+        assert!(run.should_restart(10));
+        run.generations_without_improvement = 0;
+        run.sigma = TOL_X / 10.0;
+        assert!(run.should_restart(10));
+    }
+    #[test]
+    fn ipop_doubles_the_population_on_every_restart() {
+        let config = CmaesConfig {
+            restarts: RestartStrategy::Ipop,
+            stagnation_generations: 3,
+            ..Default::default()
+        };
+        let mut strategy = CmaesStrategy::new(vec![2, 2], config);
+        let lambda = strategy.run.lambda;
+        for restart in 1..=2 {
+            for _generation in 0..4 {
+                let count = strategy.ask().len();
+                strategy.tell(worlds_with_fitness(count, 0));
+            }
+            assert_eq!(strategy.restarts, restart);
+            assert_eq!(strategy.run.lambda, lambda * 2_usize.pow(restart as u32));
+        }
+    }
+    #[test]
+    fn bipop_follows_a_small_restart_with_a_large_one() {
+        let config = CmaesConfig {
+            restarts: RestartStrategy::Bipop,
+            stagnation_generations: 3,
+            ..Default::default()
+        };
+        let mut strategy = CmaesStrategy::new(vec![2, 2], config);
+        let default_lambda = strategy.default_lambda;
+        while strategy.restarts == 0 {
+            let count = strategy.ask().len();
+            strategy.tell(worlds_with_fitness(count, 0));
+        }
+        // Nothing has been spent on small populations yet
+        assert!(strategy.run.sigma <= strategy.config.sigma);
+        assert!(strategy.run.lambda >= default_lambda);
+        assert_eq!(strategy.large_restarts, 0);
+        while strategy.large_restarts == 0 {
+            let count = strategy.ask().len();
+            strategy.tell(worlds_with_fitness(count, 0));
+        }
+        assert_eq!(strategy.run.lambda, default_lambda * 2);
+        assert_eq!(strategy.run.sigma, strategy.config.sigma);
+    }
+}
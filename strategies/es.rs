@@ -0,0 +1,197 @@
+use std::{fs, io, path::Path};
+
+use rand::Rng;
+
+use crate::{
+    POPULATION_SIZE,
+    game::world::World,
+    perceptron::{network::Network, training::Optimizer},
+    strategies::{SearchStrategy, gaussian},
+};
+
+pub struct EsConfig {
+    // Every pair is played as mean + sigma * noise and mean - sigma * noise
+    pub pairs: usize,
+    pub sigma: f32,
+    pub learning_rate: f32,
+    pub weight_decay: f32,
+}
+impl Default for EsConfig {
+    fn default() -> Self {
+        return Self {
+            pairs: POPULATION_SIZE / 2,
+            sigma: 0.1,
+            learning_rate: 0.03,
+            weight_decay: 0.005,
+        };
+    }
+}
+
+// OpenAI-ES: Gaussian search around one mean parameter vector, updated with rank-normalized fitness
+pub struct EvolutionStrategy {
+    pub config: EsConfig,
+    pub mean: Network,
+    pub generation: usize,
+    optimizer: Optimizer,
+    noise: Vec<Vec<f32>>,
+    // Both networks of a pair play the same world, so the noise of the world cancels out
+    seeds: Vec<u64>,
+}
+impl EvolutionStrategy {
+    pub fn new(layer_sizes: Vec<usize>, config: EsConfig) -> Self {
+        return Self::from_network(Network::create_random_network(layer_sizes), config);
+    }
+    pub fn from_network(mean: Network, config: EsConfig) -> Self {
+        return Self {
+            optimizer: Optimizer::adam(config.learning_rate),
+            config,
+            mean,
+            generation: 0,
+            noise: vec![],
+            seeds: vec![],
+        };
+    }
+    /*
+     * Checkpoint format: "es <generation> <sigma>" on the first line, then the mean network.
+     * Adam moments are not saved, they warm up again after a restart.
+     */
+    pub fn load_checkpoint(path: &Path, config: EsConfig) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let (header, network_text) = text.split_once('\n').unwrap_or((&text, ""));
+        let header: Vec<&str> = header.split_whitespace().collect();
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Bad ES checkpoint header");
+        if header.len() != 3 || header[0] != "es" {
+            return Err(invalid());
+        }
+        let mut strategy = Self::from_network(Network::from_text(network_text)?, config);
+        strategy.generation = header[1].parse().map_err(|_| invalid())?;
+        strategy.config.sigma = header[2].parse().map_err(|_| invalid())?;
+        return Ok(strategy);
+    }
+}
+impl SearchStrategy for EvolutionStrategy {
+    fn name(&self) -> &'static str {
+        return "Evolution strategies";
+    }
+    fn ask(&mut self) -> Vec<Network> {
+        let mut rng = rand::rng();
+        let mean = self.mean.parameters();
+        self.noise = vec![];
+        self.seeds = vec![];
+        let mut networks = vec![];
+        for _pair_id in 0..self.config.pairs {
+            let noise: Vec<f32> = (0..mean.len()).map(|_| gaussian(&mut rng)).collect();
+            let seed = rng.random();
+            for sign in [1.0, -1.0] {
+                let parameters: Vec<f32> = mean
+                    .iter()
+                    .zip(&noise)
+                    .map(|(value, noise)| value + sign * self.config.sigma * noise)
+                    .collect();
+                let mut network = self.mean.clone();
+                network.set_parameters(&parameters);
+                networks.push(network);
+                self.seeds.push(seed);
+            }
+            self.noise.push(noise);
+        }
+        return networks;
+    }
+    fn seeds(&self) -> Option<Vec<u64>> {
+        return Some(self.seeds.clone());
+    }
+    fn tell(&mut self, worlds: Vec<World>) {
+        assert_eq!(
+            worlds.len(),
+            self.noise.len() * 2,
+            "ES has to be told about every network it has asked for"
+        );
+        let fitness: Vec<usize> = worlds.iter().map(|world| world.fitness_score).collect();
+        let ranks = centered_ranks(&fitness);
+        let mut parameters = self.mean.parameters();
+        let mut gradients = vec![0.0; parameters.len()];
+        for pair_id in 0..self.noise.len() {
+            let weight = ranks[pair_id * 2] - ranks[pair_id * 2 + 1];
+            for (gradient, noise) in gradients.iter_mut().zip(&self.noise[pair_id]) {
+                *gradient += weight * noise;
+            }
+        }
+        // Optimizer minimizes, so the fitness gradient goes in with the opposite sign
+        let scale = -1.0 / (worlds.len() as f32 * self.config.sigma);
+        for (gradient, parameter) in gradients.iter_mut().zip(&parameters) {
+            *gradient = *gradient * scale + self.config.weight_decay * parameter;
+        }
+        self.optimizer.step(&mut parameters, &gradients);
+        self.mean.set_parameters(&parameters);
+        self.generation += 1;
+    }
+    fn save_checkpoint(&self, path: &Path) -> io::Result<()> {
+        let text = format!(
+            "es {} {}\n{}",
+            self.generation,
+            self.config.sigma,
+            self.mean.to_text()
+        );
+        return fs::write(path, text);
+    }
+    // The search continues around the best of the given networks
+    fn reseed(&mut self, networks: Vec<Network>) {
+        if let Some(network) = networks.into_iter().next() {
+            self.mean = network;
+        }
+    }
+}
+
+// Ranks mapped into [-0.5, 0.5], so the update doesn't care about the fitness scale.
+// Equal fitness gets the average of the ranks it spans, a tie is no signal for the update
+pub fn centered_ranks(fitness: &Vec<usize>) -> Vec<f32> {
+    let mut order: Vec<usize> = (0..fitness.len()).collect();
+    order.sort_by_key(|id| fitness[*id]);
+    let mut ranks = vec![0.0; fitness.len()];
+    if fitness.len() < 2 {
+        return ranks;
+    }
+    let mut first = 0;
+    while first < order.len() {
+        let mut last = first;
+        while last + 1 < order.len() && fitness[order[last + 1]] == fitness[order[first]] {
+            last += 1;
+        }
+        let rank = (first + last) as f32 / 2.0;
+        for id in &order[first..=last] {
+            ranks[*id] = rank / (fitness.len() - 1) as f32 - 0.5;
+        }
+        first = last + 1;
+    }
+    return ranks;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_networks_of_a_pair_play_the_same_seed() {
+        let config = EsConfig {
+            pairs: 4,
+            ..Default::default()
+        };
+        let mut strategy = EvolutionStrategy::new(vec![3, 2, 4], config);
+        let networks = strategy.ask();
+        let seeds = strategy.seeds().unwrap();
+        assert_eq!(seeds.len(), networks.len());
+        for pair in seeds.chunks(2) {
+            assert_eq!(pair[0], pair[1]);
+        }
+        assert_ne!(seeds[0], seeds[2]);
+    }
+    #[test]
+    fn equal_fitness_gets_equal_ranks() {
+        let ranks = centered_ranks(&vec![5, 1, 5, 9, 1]);
+        assert_eq!(ranks[0], ranks[2]);
+        assert_eq!(ranks[1], ranks[4]);
+        assert_eq!(ranks, vec![0.125, -0.375, 0.125, 0.5, -0.375]);
+        // A pair that ties moves the mean nowhere
+        assert!(centered_ranks(&vec![3, 3]).iter().all(|rank| *rank == 0.0));
+    }
+}
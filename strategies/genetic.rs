@@ -0,0 +1,123 @@
+use crate::{
+    game::world::World,
+    genetic::{
+        GeneticSettings,
+        crossover::recombine_worlds,
+        diversity::{DiversityStats, StagnationConfig, StagnationResponse},
+        selection::get_top_n,
+    },
+    perceptron::network::Network,
+    strategies::SearchStrategy,
+};
+
+// Tournament selection, crossover and mutation with elitism
+pub struct GeneticStrategy {
+    pub settings: GeneticSettings,
+    pub stagnation: StagnationConfig,
+    population: Vec<Network>,
+    layer_sizes: Vec<usize>,
+    best_fitness: usize,
+    generations_without_improvement: usize,
+    // Generations of hypermutation left, the normal rate comes back after them
+    hypermutation_left: usize,
+    // Given by the training loop, measured here when it hasn't been
+    diversity: Option<DiversityStats>,
+}
+impl GeneticStrategy {
+    pub fn new(layer_sizes: Vec<usize>, settings: GeneticSettings) -> Self {
+        let mut population = vec![];
+        (0..settings.population_size)
+            .for_each(|_| population.push(Network::create_random_network(layer_sizes.clone())));
+        return Self {
+            settings,
+            stagnation: StagnationConfig::default(),
+            population,
+            layer_sizes,
+            best_fitness: 0,
+            generations_without_improvement: 0,
+            hypermutation_left: 0,
+            diversity: None,
+        };
+    }
+    // Children come first in the population, so the elitists at the end are never replaced
+    fn replace_children(&mut self, count: usize) {
+        let children = self.population.len() - self.settings.elitists_num;
+        for slot in self.population.iter_mut().take(count.min(children)) {
+            *slot = Network::create_random_network(self.layer_sizes.clone());
+        }
+    }
+}
+impl SearchStrategy for GeneticStrategy {
+    fn name(&self) -> &'static str {
+        return "Genetic algorithm";
+    }
+    fn ask(&mut self) -> Vec<Network> {
+        return std::mem::take(&mut self.population);
+    }
+    fn tell(&mut self, worlds: Vec<World>) {
+        let generation_best = worlds
+            .iter()
+            .map(|world| world.fitness_score)
+            .max()
+            .unwrap_or(0);
+        if generation_best > self.best_fitness {
+            self.best_fitness = generation_best;
+            self.generations_without_improvement = 0;
+        } else {
+            self.generations_without_improvement += 1;
+        }
+        let diversity = self.diversity.take();
+        let mut stagnated = false;
+        if self.stagnation.response != StagnationResponse::None {
+            let diversity = diversity.unwrap_or_else(|| DiversityStats::from_worlds(&worlds));
+            let collapsed = diversity.unique_share() < self.stagnation.min_unique_share;
+            let plateaued =
+                self.generations_without_improvement >= self.stagnation.plateau_generations;
+            stagnated = collapsed || plateaued;
+        }
+
+        let mut settings = self.settings;
+        if stagnated && self.stagnation.response == StagnationResponse::Hypermutation {
+            self.hypermutation_left = self.stagnation.hypermutation_generations;
+        }
+        if self.hypermutation_left > 0 {
+            settings.mutation_rate = self.stagnation.hypermutation_rate;
+            self.hypermutation_left -= 1;
+        }
+        let mut networks = recombine_worlds(&worlds, &settings);
+        networks.extend(get_top_n(worlds, self.settings.elitists_num));
+        self.population = networks;
+
+        if stagnated {
+            println!(
+                "  Stagnation, responding with {:?}",
+                self.stagnation.response
+            );
+            // Starting the plateau count over gives the response time to work
+            self.generations_without_improvement = 0;
+            match self.stagnation.response {
+                StagnationResponse::RandomImmigrants => {
+                    let count =
+                        (self.population.len() as f32 * self.stagnation.immigrant_share) as usize;
+                    self.replace_children(count);
+                }
+                StagnationResponse::Restart => self.replace_children(self.population.len()),
+                StagnationResponse::None | StagnationResponse::Hypermutation => {}
+            }
+        }
+    }
+    fn set_diversity(&mut self, diversity: DiversityStats) {
+        self.diversity = Some(diversity);
+    }
+    fn reseed(&mut self, networks: Vec<Network>) {
+        for (slot, network) in self.population.iter_mut().zip(networks) {
+            *slot = network;
+        }
+    }
+    fn mutation_rate(&self) -> Option<f32> {
+        return Some(self.settings.mutation_rate);
+    }
+    fn set_mutation_rate(&mut self, rate: f32) {
+        self.settings.mutation_rate = rate;
+    }
+}
@@ -0,0 +1,288 @@
+use rand::{Rng, seq::IteratorRandom};
+
+use crate::{
+    POPULATION_SIZE,
+    game::world::World,
+    genetic::{
+        GeneticSettings,
+        crossover::{crossover_networks, recombine_worlds},
+        selection::{get_top_n, tournament_selection},
+        statistics::GenerationStats,
+    },
+    perceptron::network::Network,
+    strategies::SearchStrategy,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MigrationTopology {
+    // Island i sends its best to island i + 1
+    Ring,
+    // Every island sends its best to every other island
+    FullyConnected,
+    // Every island sends its best to one random other island
+    Random,
+}
+impl MigrationTopology {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "ring" => Some(MigrationTopology::Ring),
+            "full" | "fully-connected" => Some(MigrationTopology::FullyConnected),
+            "random" => Some(MigrationTopology::Random),
+            _ => None,
+        };
+    }
+}
+
+pub struct IslandConfig {
+    // One entry per island
+    pub islands: Vec<GeneticSettings>,
+    pub migration_interval: usize,
+    pub migrants: usize,
+    pub topology: MigrationTopology,
+}
+impl IslandConfig {
+    // Splits the population between the islands, each one mutating at its own rate.
+    // None when there are so many islands that one would have no room for children
+    pub fn with_islands(islands_count: usize) -> Option<Self> {
+        if islands_count == 0 {
+            return None;
+        }
+        let population_size = POPULATION_SIZE / islands_count;
+        let elitists_num = (population_size / 25).max(2);
+        if population_size <= elitists_num {
+            return None;
+        }
+        let islands = (0..islands_count)
+            .map(|island_id| GeneticSettings {
+                population_size,
+                elitists_num,
+                mutation_rate: 0.01 * 2.0_f32.powi(island_id as i32 % 5),
+                tournament_size: (population_size / 10).max(2),
+            })
+            .collect();
+        return Some(Self {
+            islands,
+            migration_interval: 10,
+            migrants: 2,
+            topology: MigrationTopology::Ring,
+        });
+    }
+}
+impl Default for IslandConfig {
+    fn default() -> Self {
+        return Self::with_islands(5).unwrap();
+    }
+}
+
+struct Island {
+    settings: GeneticSettings,
+    population: Vec<Network>,
+    // How many networks the island has given out in the last ask
+    asked: usize,
+}
+
+// K populations evolving separately, exchanging their best networks every few generations
+pub struct IslandModel {
+    pub config: IslandConfig,
+    pub generation: usize,
+    islands: Vec<Island>,
+}
+impl IslandModel {
+    pub fn new(layer_sizes: Vec<usize>, config: IslandConfig) -> Self {
+        let islands = config
+            .islands
+            .iter()
+            .map(|settings| Island {
+                settings: *settings,
+                population: (0..settings.population_size)
+                    .map(|_| Network::create_random_network(layer_sizes.clone()))
+                    .collect(),
+                asked: 0,
+            })
+            .collect();
+        return Self {
+            config,
+            generation: 1,
+            islands,
+        };
+    }
+    fn destinations(&self, island_id: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let count = self.islands.len();
+        if count < 2 {
+            return vec![];
+        }
+        return match self.config.topology {
+            MigrationTopology::Ring => vec![(island_id + 1) % count],
+            MigrationTopology::FullyConnected => (0..count).filter(|id| *id != island_id).collect(),
+            MigrationTopology::Random => {
+                let other = rng.random_range(0..count - 1);
+                vec![if other >= island_id { other + 1 } else { other }]
+            }
+        };
+    }
+}
+impl SearchStrategy for IslandModel {
+    fn name(&self) -> &'static str {
+        return "Island model GA";
+    }
+    fn ask(&mut self) -> Vec<Network> {
+        let mut networks = vec![];
+        for island in &mut self.islands {
+            island.asked = island.population.len();
+            networks.extend(std::mem::take(&mut island.population));
+        }
+        return networks;
+    }
+    fn tell(&mut self, mut worlds: Vec<World>) {
+        let mut island_worlds = vec![];
+        for island in &self.islands {
+            let rest = worlds.split_off(island.asked.min(worlds.len()));
+            island_worlds.push(worlds);
+            worlds = rest;
+        }
+
+        let migrate = self
+            .generation
+            .is_multiple_of(self.config.migration_interval);
+        let mut emigrants = vec![];
+        for (island_id, worlds) in island_worlds.iter().enumerate() {
+            let stats = GenerationStats::from_worlds(self.generation, worlds);
+            println!("  Island {}: {}", island_id, stats.summary());
+            if migrate {
+                emigrants.push(get_top_n(
+                    worlds.clone(),
+                    self.config.migrants.min(worlds.len()),
+                ));
+            }
+        }
+
+        for (island, worlds) in self.islands.iter_mut().zip(island_worlds) {
+            let mut networks = recombine_worlds(&worlds, &island.settings);
+            // Children come in pairs, an odd number of them needs one more
+            if !(island.settings.population_size - island.settings.elitists_num).is_multiple_of(2) {
+                let (parent1, parent2) =
+                    tournament_selection(&worlds, island.settings.tournament_size);
+                let (child, _) = crossover_networks(
+                    &parent1.network,
+                    &parent2.network,
+                    island.settings.mutation_rate,
+                );
+                networks.push(child);
+            }
+            networks.extend(get_top_n(worlds, island.settings.elitists_num));
+            island.population = networks;
+        }
+
+        if migrate {
+            let mut rng = rand::rng();
+            let mut arrivals: Vec<Vec<Network>> = vec![vec![]; self.islands.len()];
+            for (island_id, migrants) in emigrants.into_iter().enumerate() {
+                for destination in self.destinations(island_id, &mut rng) {
+                    arrivals[destination].extend(migrants.iter().cloned());
+                }
+            }
+            for (island, migrants) in self.islands.iter_mut().zip(arrivals) {
+                // Children come first in the population, elitists at the end stay untouched.
+                // Every migrant gets its own slot, extra migrants are left out
+                let children = island.population.len() - island.settings.elitists_num;
+                let slots = (0..children).choose_multiple(&mut rng, migrants.len());
+                for (slot, migrant) in slots.into_iter().zip(migrants) {
+                    island.population[slot] = migrant;
+                }
+            }
+        }
+        self.generation += 1;
+    }
+    // Dealt out in turns, so every island gets some of the best networks
+    fn reseed(&mut self, networks: Vec<Network>) {
+        let islands_count = self.islands.len();
+        for (network_id, network) in networks.into_iter().enumerate() {
+            let island = &mut self.islands[network_id % islands_count];
+            let slot = network_id / islands_count;
+            if slot < island.population.len() {
+                island.population[slot] = network;
+            }
+        }
+    }
+    // Rate of the first island, the others keep their ratio to it
+    fn mutation_rate(&self) -> Option<f32> {
+        return self
+            .islands
+            .first()
+            .map(|island| island.settings.mutation_rate);
+    }
+    fn set_mutation_rate(&mut self, rate: f32) {
+        let Some(first_rate) = self.mutation_rate() else {
+            return;
+        };
+        let scale = if first_rate > 0.0 {
+            rate / first_rate
+        } else {
+            1.0
+        };
+        for island in &mut self.islands {
+            island.settings.mutation_rate = if first_rate > 0.0 {
+                (island.settings.mutation_rate * scale).min(1.0)
+            } else {
+                rate
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_migrant_arrives_and_islands_keep_their_size() {
+        let settings = GeneticSettings {
+            population_size: 7,
+            elitists_num: 2,
+            mutation_rate: 0.05,
+            tournament_size: 3,
+        };
+        let config = IslandConfig {
+            islands: vec![settings; 3],
+            migration_interval: 1,
+            migrants: 2,
+            topology: MigrationTopology::FullyConnected,
+        };
+        let mut model = IslandModel::new(vec![4, 3, 4], config);
+        let worlds: Vec<World> = model
+            .ask()
+            .into_iter()
+            .enumerate()
+            .map(|(world_id, network)| {
+                let mut world = World::with_seed(10, 10, network, 0);
+                world.fitness_score = world_id;
+                world
+            })
+            .collect();
+        // The best two of every island are its last two networks
+        let best: Vec<Network> = worlds
+            .iter()
+            .filter(|world| world.fitness_score % 7 >= 5)
+            .map(|world| world.network.clone())
+            .collect();
+        model.tell(worlds);
+        for (island_id, island) in model.islands.iter().enumerate() {
+            assert_eq!(island.population.len(), 7);
+            for (network_id, network) in best.iter().enumerate() {
+                if network_id / 2 != island_id {
+                    assert!(island.population.contains(network));
+                }
+            }
+        }
+    }
+    #[test]
+    fn every_island_has_room_for_children() {
+        assert!(IslandConfig::with_islands(0).is_none());
+        assert!(IslandConfig::with_islands(POPULATION_SIZE).is_none());
+        assert!(IslandConfig::with_islands(POPULATION_SIZE / 2).is_none());
+        let config = IslandConfig::with_islands(POPULATION_SIZE / 3).unwrap();
+        for settings in config.islands {
+            assert!(settings.population_size > settings.elitists_num);
+        }
+    }
+}
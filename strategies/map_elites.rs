@@ -0,0 +1,183 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use rand::Rng;
+
+use crate::{
+    MUTATION_RATE, POPULATION_SIZE,
+    game::{behaviour::Behaviour, world::World},
+    genetic::crossover::crossover_networks,
+    perceptron::network::Network,
+    strategies::SearchStrategy,
+};
+
+// Every cell of the grid is drawn as a square of this many pixels in the heatmap
+const HEATMAP_CELL_PIXELS: usize = 16;
+
+pub struct MapElitesConfig {
+    // The grid is bins x bins cells: board coverage along x, turn frequency along y
+    pub bins: usize,
+    // Offspring are mutated copies of random elites
+    pub batch_size: usize,
+    pub mutation_rate: f32,
+}
+impl Default for MapElitesConfig {
+    fn default() -> Self {
+        return Self {
+            bins: 20,
+            batch_size: POPULATION_SIZE,
+            mutation_rate: MUTATION_RATE * 5.0,
+        };
+    }
+}
+
+#[derive(Clone)]
+pub struct Elite {
+    pub network: Network,
+    pub fitness: usize,
+    pub apples: usize,
+}
+
+// Keeps the best network found for every kind of behaviour instead of one best network overall
+pub struct MapElites {
+    pub config: MapElitesConfig,
+    pub generation: usize,
+    // bins * bins cells, row by row
+    pub grid: Vec<Option<Elite>>,
+    layer_sizes: Vec<usize>,
+}
+impl MapElites {
+    pub fn new(layer_sizes: Vec<usize>, config: MapElitesConfig) -> Self {
+        return Self {
+            grid: vec![None; config.bins * config.bins],
+            config,
+            generation: 1,
+            layer_sizes,
+        };
+    }
+    pub fn cell_id(&self, behaviour: &Behaviour) -> usize {
+        let bin =
+            |value: f32| ((value * self.config.bins as f32) as usize).min(self.config.bins - 1);
+        // Most episodes only see a small part of the board, the square root spreads them out
+        return bin(behaviour.turn_frequency) * self.config.bins + bin(behaviour.coverage.sqrt());
+    }
+    pub fn filled_cells(&self) -> usize {
+        return self.grid.iter().filter(|cell| cell.is_some()).count();
+    }
+    /*
+     * Binary PPM image of the grid, square-rooted coverage grows to the right and turn frequency downwards.
+     * Empty cells are black, filled ones go from blue to yellow with the fitness of their elite.
+     */
+    pub fn save_heatmap(&self, path: &Path) -> io::Result<()> {
+        let bins = self.config.bins;
+        let size = bins * HEATMAP_CELL_PIXELS;
+        let best_fitness = self
+            .grid
+            .iter()
+            .flatten()
+            .map(|elite| elite.fitness)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let mut writer = BufWriter::new(File::create(path)?);
+        write!(writer, "P6\n{} {}\n255\n", size, size)?;
+        for y in 0..size {
+            for x in 0..size {
+                let cell = &self.grid[(y / HEATMAP_CELL_PIXELS) * bins + x / HEATMAP_CELL_PIXELS];
+                let pixel = match cell {
+                    Some(elite) => {
+                        let heat = elite.fitness as f32 / best_fitness as f32;
+                        [
+                            (255.0 * heat) as u8,
+                            (255.0 * heat) as u8,
+                            (255.0 * (1.0 - heat)) as u8,
+                        ]
+                    }
+                    None => [0, 0, 0],
+                };
+                writer.write_all(&pixel)?;
+            }
+        }
+        return writer.flush();
+    }
+}
+impl SearchStrategy for MapElites {
+    fn name(&self) -> &'static str {
+        return "MAP-Elites";
+    }
+    fn ask(&mut self) -> Vec<Network> {
+        let elites: Vec<&Elite> = self.grid.iter().flatten().collect();
+        if elites.is_empty() {
+            return (0..self.config.batch_size)
+                .map(|_| Network::create_random_network(self.layer_sizes.clone()))
+                .collect();
+        }
+        let mut rng = rand::rng();
+        let mut networks = vec![];
+        for _network_id in 0..self.config.batch_size {
+            let parent1 = elites[rng.random_range(0..elites.len())];
+            let parent2 = elites[rng.random_range(0..elites.len())];
+            let (child, _) = crossover_networks(
+                &parent1.network,
+                &parent2.network,
+                self.config.mutation_rate,
+            );
+            networks.push(child);
+        }
+        return networks;
+    }
+    fn tell(&mut self, worlds: Vec<World>) {
+        let mut improved = 0;
+        for world in worlds {
+            let cell_id = self.cell_id(&Behaviour::from_recording(&world.recording));
+            let is_better = match &self.grid[cell_id] {
+                Some(elite) => world.fitness_score > elite.fitness,
+                None => true,
+            };
+            if is_better {
+                improved += 1;
+                self.grid[cell_id] = Some(Elite {
+                    fitness: world.fitness_score,
+                    apples: world.score,
+                    network: world.network,
+                });
+            }
+        }
+        println!(
+            "  MAP-Elites: {}/{} cells filled, {} improved",
+            self.filled_cells(),
+            self.grid.len(),
+            improved
+        );
+        self.generation += 1;
+    }
+    /*
+     * The grid as text: one "<x> <y> <fitness> <apples>" line per filled cell.
+     * The heatmap image is written next to it with the .ppm extension.
+     */
+    fn save_checkpoint(&self, path: &Path) -> io::Result<()> {
+        let mut text = format!("map-elites {} {}\n", self.generation, self.config.bins);
+        for (cell_id, cell) in self.grid.iter().enumerate() {
+            if let Some(elite) = cell {
+                text += &format!(
+                    "{} {} {} {}\n",
+                    cell_id % self.config.bins,
+                    cell_id / self.config.bins,
+                    elite.fitness,
+                    elite.apples
+                );
+            }
+        }
+        fs::write(path, text)?;
+        return self.save_heatmap(&path.with_extension("ppm"));
+    }
+    fn mutation_rate(&self) -> Option<f32> {
+        return Some(self.config.mutation_rate);
+    }
+    fn set_mutation_rate(&mut self, rate: f32) {
+        self.config.mutation_rate = rate;
+    }
+}
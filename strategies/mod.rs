@@ -0,0 +1,71 @@
+use std::{f32::consts::PI, io, path::Path};
+
+use rand::Rng;
+
+use crate::{game::world::World, genetic::diversity::DiversityStats, perceptron::network::Network};
+
+pub mod cmaes;
+pub mod es;
+pub mod genetic;
+pub mod islands;
+pub mod map_elites;
+pub mod novelty;
+
+// Anything that can propose networks for a generation and learn from how they played
+pub trait SearchStrategy: Send {
+    fn name(&self) -> &'static str;
+    // Networks to be played in the next generation
+    fn ask(&mut self) -> Vec<Network>;
+    // Seeds of the worlds the asked networks play on, in the same order, None for random ones
+    fn seeds(&self) -> Option<Vec<u64>> {
+        return None;
+    }
+    // Diversity of the worlds about to be told, given before `tell` so it is measured once
+    fn set_diversity(&mut self, _diversity: DiversityStats) {}
+    // Finished worlds of the generation, in the same order `ask` has returned the networks
+    fn tell(&mut self, worlds: Vec<World>);
+    // Unsupported for strategies that have nothing worth saving
+    fn save_checkpoint(&self, _path: &Path) -> io::Result<()> {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} has no checkpoints", self.name()),
+        ));
+    }
+    // Known good networks to start from, strategies without a population may ignore them
+    fn reseed(&mut self, _networks: Vec<Network>) {}
+    // None for strategies that don't mutate
+    fn mutation_rate(&self) -> Option<f32> {
+        return None;
+    }
+    fn set_mutation_rate(&mut self, _rate: f32) {}
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StrategyKind {
+    Genetic,
+    EvolutionStrategies,
+    Cmaes,
+    Islands,
+    Novelty,
+    MapElites,
+}
+impl StrategyKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "ga" | "genetic" => Some(StrategyKind::Genetic),
+            "es" => Some(StrategyKind::EvolutionStrategies),
+            "cmaes" | "cma-es" => Some(StrategyKind::Cmaes),
+            "islands" => Some(StrategyKind::Islands),
+            "novelty" => Some(StrategyKind::Novelty),
+            "map-elites" => Some(StrategyKind::MapElites),
+            _ => None,
+        };
+    }
+}
+
+// Standard normal sample (Box-Muller)
+pub fn gaussian(rng: &mut impl Rng) -> f32 {
+    let u1: f32 = 1.0 - rng.random::<f32>();
+    let u2: f32 = rng.random();
+    return (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos();
+}
@@ -0,0 +1,119 @@
+use crate::{
+    game::{
+        behaviour::{Behaviour, distance},
+        world::World,
+    },
+    genetic::{GeneticSettings, crossover::recombine_worlds, selection::get_top_n},
+    perceptron::network::Network,
+    strategies::SearchStrategy,
+};
+
+pub struct NoveltyConfig {
+    pub settings: GeneticSettings,
+    // Novelty is the mean distance to this many nearest behaviours
+    pub neighbours: usize,
+    // The most novel behaviours of every generation are kept in the archive
+    pub archive_additions: usize,
+    // Oldest behaviours are dropped once the archive grows over this
+    pub archive_limit: usize,
+}
+impl Default for NoveltyConfig {
+    fn default() -> Self {
+        return Self {
+            settings: GeneticSettings::default(),
+            neighbours: 15,
+            archive_additions: 5,
+            archive_limit: 5000,
+        };
+    }
+}
+
+// The GA selects for behaviours that differ from everything seen so far instead of for fitness
+pub struct NoveltySearch {
+    pub config: NoveltyConfig,
+    pub generation: usize,
+    archive: Vec<Vec<f32>>,
+    population: Vec<Network>,
+}
+impl NoveltySearch {
+    pub fn new(layer_sizes: Vec<usize>, config: NoveltyConfig) -> Self {
+        return Self {
+            population: (0..config.settings.population_size)
+                .map(|_| Network::create_random_network(layer_sizes.clone()))
+                .collect(),
+            config,
+            generation: 1,
+            archive: vec![],
+        };
+    }
+    // Mean distance to the k nearest behaviours of the generation and the archive
+    fn novelty(&self, descriptor_id: usize, descriptors: &Vec<Vec<f32>>) -> f32 {
+        let descriptor = &descriptors[descriptor_id];
+        let mut distances: Vec<f32> = descriptors
+            .iter()
+            .enumerate()
+            .filter(|(other_id, _)| *other_id != descriptor_id)
+            .map(|(_, other)| distance(descriptor, other))
+            .chain(self.archive.iter().map(|other| distance(descriptor, other)))
+            .collect();
+        if distances.is_empty() {
+            return 0.0;
+        }
+        let neighbours = self.config.neighbours.clamp(1, distances.len());
+        distances.select_nth_unstable_by(neighbours - 1, |a, b| a.total_cmp(b));
+        return distances[..neighbours].iter().sum::<f32>() / neighbours as f32;
+    }
+}
+impl SearchStrategy for NoveltySearch {
+    fn name(&self) -> &'static str {
+        return "Novelty search";
+    }
+    fn ask(&mut self) -> Vec<Network> {
+        return std::mem::take(&mut self.population);
+    }
+    fn tell(&mut self, mut worlds: Vec<World>) {
+        let descriptors: Vec<Vec<f32>> = worlds
+            .iter()
+            .map(|world| Behaviour::from_recording(&world.recording).descriptor())
+            .collect();
+        let novelty: Vec<f32> = (0..descriptors.len())
+            .map(|descriptor_id| self.novelty(descriptor_id, &descriptors))
+            .collect();
+        println!(
+            "  Novelty: mean {:.4}, max {:.4}, archive {}",
+            novelty.iter().sum::<f32>() / novelty.len().max(1) as f32,
+            novelty.iter().cloned().fold(0.0, f32::max),
+            self.archive.len()
+        );
+
+        let mut order: Vec<usize> = (0..novelty.len()).collect();
+        order.sort_by(|id1, id2| novelty[*id2].total_cmp(&novelty[*id1]));
+        for descriptor_id in order.iter().take(self.config.archive_additions) {
+            self.archive.push(descriptors[*descriptor_id].clone());
+        }
+        if self.archive.len() > self.config.archive_limit {
+            let excess = self.archive.len() - self.config.archive_limit;
+            self.archive.drain(..excess);
+        }
+
+        // Selection only looks at fitness_score, so novelty takes its place from here on
+        for (world, novelty) in worlds.iter_mut().zip(novelty) {
+            world.fitness_score = (novelty * 1_000_000.0) as usize;
+        }
+        let mut networks = recombine_worlds(&worlds, &self.config.settings);
+        networks.extend(get_top_n(worlds, self.config.settings.elitists_num));
+        self.population = networks;
+        self.generation += 1;
+    }
+    fn reseed(&mut self, networks: Vec<Network>) {
+        for (slot, network) in self.population.iter_mut().zip(networks) {
+            *slot = network;
+        }
+    }
+    fn mutation_rate(&self) -> Option<f32> {
+        return Some(self.config.settings.mutation_rate);
+    }
+    fn set_mutation_rate(&mut self, rate: f32) {
+        self.config.settings.mutation_rate = rate;
+    }
+}
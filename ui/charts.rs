@@ -0,0 +1,175 @@
+use macroquad::{
+    color::*,
+    shapes::{draw_line, draw_rectangle, draw_rectangle_lines},
+    text::draw_text,
+};
+
+use crate::{SCREEN_SIZE, game::world::DeathCause, genetic::statistics::GenerationStats};
+
+// Charts only show the latest generations, older ones get squeezed into nothing anyway
+const CHART_GENERATIONS: usize = 200;
+const CHARTS_TOP: f32 = 100.0;
+const CHART_MARGIN: f32 = 10.0;
+const CHART_FONT_SIZE: f32 = 18.0;
+const DEATH_COLORS: [Color; 4] = [RED, ORANGE, PURPLE, DARKBROWN];
+
+// Everything the trainer has reported about finished generations
+#[derive(Default)]
+pub struct ChartHistory {
+    pub stats: Vec<GenerationStats>,
+    pub generations_per_second: Vec<f32>,
+}
+impl ChartHistory {
+    pub fn push(&mut self, stats: GenerationStats, generations_per_second: f32) {
+        self.stats.push(stats);
+        self.generations_per_second.push(generations_per_second);
+    }
+}
+
+struct Series<'a> {
+    name: &'a str,
+    color: Color,
+    values: Vec<f32>,
+}
+
+// Four charts in a 2x2 grid under the text lines
+pub fn draw_charts(history: &ChartHistory) {
+    let width = (SCREEN_SIZE - CHART_MARGIN * 3.0) / 2.0;
+    let height = (SCREEN_SIZE - CHARTS_TOP - CHART_MARGIN * 3.0) / 2.0;
+    let left = CHART_MARGIN;
+    let right = CHART_MARGIN * 2.0 + width;
+    let top = CHARTS_TOP + CHART_MARGIN;
+    let bottom = CHARTS_TOP + CHART_MARGIN * 2.0 + height;
+
+    let start = history.stats.len().saturating_sub(CHART_GENERATIONS);
+    let stats = &history.stats[start..];
+    let series = |name, color, value: fn(&GenerationStats) -> f32| Series {
+        name,
+        color,
+        values: stats.iter().map(value).collect(),
+    };
+    draw_line_chart(
+        left,
+        top,
+        width,
+        height,
+        "Fitness",
+        &[
+            series("best", GREEN, |stats| stats.best_fitness as f32),
+            series("mean", YELLOW, |stats| stats.mean_fitness),
+            series("median", SKYBLUE, |stats| stats.median_fitness),
+        ],
+    );
+    draw_line_chart(
+        right,
+        top,
+        width,
+        height,
+        "Apples",
+        &[
+            series("best", GREEN, |stats| stats.best_apples as f32),
+            series("mean", YELLOW, |stats| stats.mean_apples),
+        ],
+    );
+    draw_deaths_chart(left, bottom, width, height, stats);
+    draw_line_chart(
+        right,
+        bottom,
+        width,
+        height,
+        "Generations per second",
+        &[Series {
+            name: "",
+            color: WHITE,
+            values: history.generations_per_second[start..].to_vec(),
+        }],
+    );
+}
+fn draw_chart_frame(x: f32, y: f32, width: f32, height: f32, title: &str) {
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+    draw_rectangle_lines(x, y, width, height, 1.0, LIGHTGRAY);
+    draw_text(title, x + 5.0, y + CHART_FONT_SIZE, CHART_FONT_SIZE, WHITE);
+}
+fn draw_line_chart(x: f32, y: f32, width: f32, height: f32, title: &str, series: &[Series]) {
+    draw_chart_frame(x, y, width, height, title);
+    let max = series
+        .iter()
+        .flat_map(|series| series.values.iter())
+        .cloned()
+        .filter(|value| value.is_finite())
+        .fold(0.0, f32::max);
+    draw_text(
+        &format!("{:.1}", max),
+        x + width - 60.0,
+        y + CHART_FONT_SIZE,
+        CHART_FONT_SIZE,
+        LIGHTGRAY,
+    );
+    // Plot area between the title and the legend
+    let plot_top = y + CHART_FONT_SIZE * 1.5;
+    let plot_height = height - CHART_FONT_SIZE * 3.0;
+    let to_screen = |index: usize, count: usize, value: f32| {
+        let share = if count > 1 {
+            index as f32 / (count - 1) as f32
+        } else {
+            0.0
+        };
+        let level = if max > 0.0 { value / max } else { 0.0 };
+        return (
+            x + 5.0 + share * (width - 10.0),
+            plot_top + plot_height * (1.0 - level.clamp(0.0, 1.0)),
+        );
+    };
+    let mut legend_x = x + 5.0;
+    for series in series {
+        let count = series.values.len();
+        for index in 1..count {
+            let (x1, y1) = to_screen(index - 1, count, series.values[index - 1]);
+            let (x2, y2) = to_screen(index, count, series.values[index]);
+            draw_line(x1, y1, x2, y2, 2.0, series.color);
+        }
+        if !series.name.is_empty() {
+            draw_text(
+                series.name,
+                legend_x,
+                y + height - 5.0,
+                CHART_FONT_SIZE,
+                series.color,
+            );
+            legend_x += series.name.len() as f32 * CHART_FONT_SIZE * 0.5 + 10.0;
+        }
+    }
+}
+// Share of every death cause in the population, one stacked column per generation
+fn draw_deaths_chart(x: f32, y: f32, width: f32, height: f32, stats: &[GenerationStats]) {
+    draw_chart_frame(x, y, width, height, "Death causes");
+    let plot_top = y + CHART_FONT_SIZE * 1.5;
+    let plot_height = height - CHART_FONT_SIZE * 3.0;
+    let column_width = (width - 10.0) / stats.len().max(1) as f32;
+    for (column, generation) in stats.iter().enumerate() {
+        let total = generation.deaths.iter().sum::<usize>().max(1) as f32;
+        let mut column_top = plot_top;
+        for (cause_id, deaths) in generation.deaths.iter().enumerate() {
+            let column_height = plot_height * *deaths as f32 / total;
+            draw_rectangle(
+                x + 5.0 + column as f32 * column_width,
+                column_top,
+                column_width.max(1.0),
+                column_height,
+                DEATH_COLORS[cause_id],
+            );
+            column_top += column_height;
+        }
+    }
+    let mut legend_x = x + 5.0;
+    for (cause_id, cause) in DeathCause::ALL.iter().enumerate() {
+        draw_text(
+            cause.name(),
+            legend_x,
+            y + height - 5.0,
+            CHART_FONT_SIZE,
+            DEATH_COLORS[cause_id],
+        );
+        legend_x += cause.name().len() as f32 * CHART_FONT_SIZE * 0.45 + 10.0;
+    }
+}
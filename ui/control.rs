@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use crate::{
+    game::{behaviour::distance, world::World},
+    perceptron::network::Network,
+    strategies::SearchStrategy,
+};
+
+// Slowest the window can make the training, per tick
+const MAX_TICK_DELAY: Duration = Duration::from_millis(500);
+
+// Which world the window shows
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DisplayMode {
+    // The world chosen with the keys or the grid, the last one if none is chosen
+    Chosen,
+    // Living world with the most apples, the longest living one among equals
+    BestLiving,
+    // Previous generation's champion replayed by the window at its own pace
+    ChampionReplay,
+    // Descendants of the world that was followed when the mode was switched on
+    Lineage,
+}
+impl DisplayMode {
+    pub fn next(&self) -> DisplayMode {
+        return match self {
+            DisplayMode::Chosen => DisplayMode::BestLiving,
+            DisplayMode::BestLiving => DisplayMode::ChampionReplay,
+            DisplayMode::ChampionReplay => DisplayMode::Lineage,
+            DisplayMode::Lineage => DisplayMode::Chosen,
+        };
+    }
+    pub fn name(&self) -> &'static str {
+        return match self {
+            DisplayMode::Chosen => "Chosen world",
+            DisplayMode::BestLiving => "Best living world",
+            DisplayMode::ChampionReplay => "Champion replay",
+            DisplayMode::Lineage => "Lineage",
+        };
+    }
+}
+
+// What the window asks the trainer to do
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ControlCommand {
+    TogglePause,
+    Faster,
+    Slower,
+    SaveCheckpoint,
+    FollowWorld(usize),
+    SetMutationRate(f32),
+    SetDisplayMode(DisplayMode),
+}
+
+// Trainer side state the commands change
+pub struct ControlState {
+    pub paused: bool,
+    // Waited after every tick, zero trains at full speed
+    pub tick_delay: Duration,
+    // None follows the last world, like before there were controls
+    pub followed_world: Option<usize>,
+    pub save_requested: bool,
+    pub display_mode: DisplayMode,
+    // Network of the lineage member followed last
+    lineage: Option<Network>,
+}
+impl Default for ControlState {
+    fn default() -> Self {
+        return Self {
+            paused: false,
+            tick_delay: Duration::ZERO,
+            followed_world: None,
+            save_requested: false,
+            display_mode: DisplayMode::Chosen,
+            lineage: None,
+        };
+    }
+}
+impl ControlState {
+    pub fn apply(
+        &mut self,
+        command: ControlCommand,
+        strategy: &mut dyn SearchStrategy,
+        worlds: &Vec<World>,
+    ) {
+        match command {
+            ControlCommand::TogglePause => self.paused = !self.paused,
+            ControlCommand::Faster => {
+                self.tick_delay /= 2;
+                if self.tick_delay < Duration::from_millis(1) {
+                    self.tick_delay = Duration::ZERO;
+                }
+            }
+            ControlCommand::Slower => {
+                self.tick_delay = (self.tick_delay * 2)
+                    .max(Duration::from_millis(1))
+                    .min(MAX_TICK_DELAY);
+            }
+            ControlCommand::SaveCheckpoint => self.save_requested = true,
+            ControlCommand::FollowWorld(world_id) => {
+                self.followed_world = Some(world_id);
+                self.display_mode = DisplayMode::Chosen;
+            }
+            ControlCommand::SetMutationRate(rate) => {
+                strategy.set_mutation_rate(rate.clamp(0.0, 1.0))
+            }
+            ControlCommand::SetDisplayMode(mode) => {
+                if mode == DisplayMode::Lineage {
+                    let world_id = self.followed_world(worlds);
+                    self.lineage = worlds.get(world_id).map(|world| world.network.clone());
+                }
+                self.display_mode = mode;
+            }
+        }
+    }
+    // Index of the world to send to the window
+    pub fn followed_world(&self, worlds: &Vec<World>) -> usize {
+        let last = worlds.len().saturating_sub(1);
+        if self.display_mode == DisplayMode::BestLiving {
+            return (0..worlds.len())
+                .max_by_key(|world_id| {
+                    let world = &worlds[*world_id];
+                    (world.alive, world.score, world.life_time)
+                })
+                .unwrap_or(last);
+        }
+        return self.followed_world.unwrap_or(last).min(last);
+    }
+    /*
+     * Networks carry no ids, so the lineage goes on with the network of the new generation
+     * closest to the last followed one. An elitist is the same network, a child is its parent
+     * with a few neurons changed.
+     */
+    pub fn on_new_generation(&mut self, worlds: &Vec<World>) {
+        if self.display_mode != DisplayMode::Lineage {
+            return;
+        }
+        let Some(lineage) = &self.lineage else {
+            return;
+        };
+        let parameters = lineage.parameters();
+        let distances: Vec<f32> = worlds
+            .iter()
+            .map(|world| distance(&parameters, &world.network.parameters()))
+            .collect();
+        let closest =
+            (0..worlds.len()).min_by(|id1, id2| distances[*id1].total_cmp(&distances[*id2]));
+        if let Some(world_id) = closest {
+            self.followed_world = Some(world_id);
+            self.lineage = Some(worlds[world_id].network.clone());
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use macroquad::{
+    color::*,
+    math::vec2,
+    shapes::{draw_rectangle, draw_rectangle_lines},
+    text::draw_text,
+};
+
+use crate::{
+    SCREEN_SIZE,
+    game::{Point, world::World},
+    ui::draw_board,
+};
+
+// The grid shows this many worlds per row and column
+pub const GRID_SIDE: usize = 10;
+
+// Just enough of a world to draw it
+#[derive(Clone)]
+pub struct WorldThumbnail {
+    pub snake: Vec<Point>,
+    pub apple: Point,
+    pub obstacles: Vec<Point>,
+    pub width: i32,
+    pub height: i32,
+    pub alive: bool,
+    pub score: usize,
+}
+impl WorldThumbnail {
+    pub fn from_world(world: &World) -> Self {
+        return Self {
+            snake: world.snake.points.clone(),
+            apple: world.apple.position,
+            obstacles: world.obstacles.clone(),
+            width: world.width,
+            height: world.height,
+            alive: world.alive,
+            score: world.score,
+        };
+    }
+}
+
+fn tile_size() -> f32 {
+    return SCREEN_SIZE / GRID_SIDE as f32;
+}
+pub fn draw_grid(thumbnails: &Vec<WorldThumbnail>) {
+    let tile_size = tile_size();
+    for (thumbnail_id, thumbnail) in thumbnails.iter().take(GRID_SIDE * GRID_SIDE).enumerate() {
+        let x = (thumbnail_id % GRID_SIDE) as f32 * tile_size;
+        let y = (thumbnail_id / GRID_SIDE) as f32 * tile_size;
+        let cell_size = tile_size / thumbnail.width.max(thumbnail.height).max(1) as f32;
+        draw_board(
+            &thumbnail.snake,
+            thumbnail.apple,
+            &thumbnail.obstacles,
+            vec2(x, y),
+            cell_size,
+            thumbnail.width,
+            thumbnail.height,
+        );
+        if !thumbnail.alive {
+            draw_rectangle(x, y, tile_size, tile_size, Color::new(0.3, 0.3, 0.3, 0.7));
+        }
+        draw_rectangle_lines(x, y, tile_size, tile_size, 1.0, BLACK);
+        draw_text(
+            &thumbnail.score.to_string(),
+            x + cell_size * 2.0,
+            y + cell_size * 2.0 + 12.0,
+            16.0,
+            WHITE,
+        );
+    }
+}
+// Index of the thumbnail under the given window position
+pub fn thumbnail_at(x: f32, y: f32, thumbnails_count: usize) -> Option<usize> {
+    if x < 0.0 || y < 0.0 || x >= SCREEN_SIZE || y >= SCREEN_SIZE {
+        return None;
+    }
+    let thumbnail_id = (y / tile_size()) as usize * GRID_SIDE + (x / tile_size()) as usize;
+    if thumbnail_id < thumbnails_count.min(GRID_SIDE * GRID_SIDE) {
+        return Some(thumbnail_id);
+    }
+    return None;
+}
@@ -0,0 +1,356 @@
+use std::{
+    sync::Arc,
+    thread::{self},
+    time::Duration,
+};
+
+use macroquad::Window;
+use macroquad::input::{
+    KeyCode, MouseButton, is_key_pressed, is_mouse_button_pressed, mouse_position,
+};
+use macroquad::time::get_time;
+use macroquad::window::{Conf, clear_background, next_frame};
+use macroquad::{
+    color::*,
+    math::Vec2,
+    shapes::{draw_rectangle, draw_rectangle_lines},
+    text::draw_text,
+};
+use tokio::sync::{
+    mpsc::{Receiver, UnboundedSender},
+    watch,
+};
+
+use crate::{
+    CELL_SIZE, SCREEN_SIZE,
+    game::{Point, replay::Replay, world::World},
+    genetic::statistics::GenerationStats,
+    perceptron::network::Network,
+    ui::{
+        charts::ChartHistory,
+        control::{ControlCommand, DisplayMode},
+        grid::WorldThumbnail,
+        snapshot::WorldSnapshot,
+    },
+};
+
+// Every +/- press multiplies or divides the mutation rate by this
+const MUTATION_RATE_STEP: f32 = 1.25;
+// Champion replays play at this pace, however fast the training goes
+const REPLAY_TICKS_PER_SECOND: f64 = 15.0;
+
+pub mod charts;
+pub mod control;
+pub mod grid;
+pub mod network_panel;
+pub mod sensors;
+pub mod snapshot;
+pub mod terminal;
+
+// Where the trainer shows the followed world
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UiKind {
+    Window,
+    // Coloured half blocks in the terminal, for SSH sessions
+    Terminal,
+    // Plain characters for terminals without colours or Unicode
+    Ascii,
+    Headless,
+}
+impl UiKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "window" => Some(UiKind::Window),
+            "terminal" => Some(UiKind::Terminal),
+            "ascii" => Some(UiKind::Ascii),
+            "none" => Some(UiKind::Headless),
+            _ => None,
+        };
+    }
+}
+
+pub fn open_window(
+    frames: watch::Receiver<Option<GameState>>,
+    reports: Receiver<GenerationReport>,
+    control: UnboundedSender<ControlCommand>,
+) {
+    thread::spawn(|| {
+        Window::from_config(window_conf(), draw(frames, reports, control));
+    });
+}
+
+fn window_conf() -> Conf {
+    Conf {
+        window_width: SCREEN_SIZE as i32,
+        window_height: SCREEN_SIZE as i32,
+        window_resizable: false,
+        window_title: String::from("Python perceptron"),
+        ..Default::default()
+    }
+}
+pub async fn draw(
+    mut frames: watch::Receiver<Option<GameState>>,
+    mut reports: Receiver<GenerationReport>,
+    control: UnboundedSender<ControlCommand>,
+) {
+    let mut show_network = false;
+    let mut show_grid = false;
+    let mut show_sensors = false;
+    let mut show_charts = false;
+    let mut history = ChartHistory::default();
+    let mut latest_champion: Option<(World, Arc<Network>)> = None;
+    let mut champion_replay: Option<(Replay, Arc<Network>)> = None;
+    let mut last_replay_tick = get_time();
+    let mut trainer_finished = false;
+    loop {
+        // The last frame stays readable after the trainer is gone, so it is kept on screen
+        if frames.has_changed().is_err() {
+            trainer_finished = true;
+        }
+        while let Ok(report) = reports.try_recv() {
+            history.push(report.stats, report.generations_per_second);
+            let network = Arc::new(report.champion.network.clone());
+            latest_champion = Some((report.champion, network));
+        }
+        let Some(gamestate) = frames.borrow_and_update().clone() else {
+            clear_background(GRAY);
+            let message = if trainer_finished {
+                "Training has finished"
+            } else {
+                "Waiting for the trainer"
+            };
+            draw_text(message, 0.0, 50.0, 40.0, BLUE);
+            next_frame().await;
+            continue;
+        };
+        if is_key_pressed(KeyCode::N) {
+            show_network = !show_network;
+        }
+        if is_key_pressed(KeyCode::S) {
+            show_sensors = !show_sensors;
+        }
+        if is_key_pressed(KeyCode::C) {
+            show_charts = !show_charts;
+        }
+        if is_key_pressed(KeyCode::G) {
+            show_grid = !show_grid;
+        }
+        // The trainer may be gone already, then there is nobody to control
+        let send = |command| {
+            let _ = control.send(command);
+        };
+        if is_key_pressed(KeyCode::Space) {
+            send(ControlCommand::TogglePause);
+        }
+        if is_key_pressed(KeyCode::Up) {
+            send(ControlCommand::Faster);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            send(ControlCommand::Slower);
+        }
+        if is_key_pressed(KeyCode::M) {
+            send(ControlCommand::SetDisplayMode(
+                gamestate.display_mode.next(),
+            ));
+        }
+        if is_key_pressed(KeyCode::K) {
+            send(ControlCommand::SaveCheckpoint);
+        }
+        if is_key_pressed(KeyCode::LeftBracket) {
+            send(ControlCommand::FollowWorld(
+                gamestate.followed_world.saturating_sub(1),
+            ));
+        }
+        if is_key_pressed(KeyCode::RightBracket) {
+            send(ControlCommand::FollowWorld(gamestate.followed_world + 1));
+        }
+        if let Some(rate) = gamestate.mutation_rate {
+            if is_key_pressed(KeyCode::Equal) {
+                send(ControlCommand::SetMutationRate(rate * MUTATION_RATE_STEP));
+            }
+            if is_key_pressed(KeyCode::Minus) {
+                send(ControlCommand::SetMutationRate(rate / MUTATION_RATE_STEP));
+            }
+        }
+        clear_background(GRAY);
+        if show_grid {
+            grid::draw_grid(&gamestate.thumbnails);
+            if is_mouse_button_pressed(MouseButton::Left) {
+                let (x, y) = mouse_position();
+                if let Some(thumbnail_id) = grid::thumbnail_at(x, y, gamestate.thumbnails.len()) {
+                    send(ControlCommand::FollowWorld(thumbnail_id));
+                    show_grid = false;
+                }
+            }
+        } else if gamestate.display_mode == DisplayMode::ChampionReplay {
+            if get_time() - last_replay_tick >= 1.0 / REPLAY_TICKS_PER_SECOND {
+                last_replay_tick = get_time();
+                let playing = match &mut champion_replay {
+                    Some((replay, _)) => replay.tick().unwrap_or(false),
+                    None => false,
+                };
+                // A finished replay starts over with the newest champion
+                if !playing {
+                    champion_replay = latest_champion.as_ref().map(|(champion, network)| {
+                        let mut replay = Replay::new(champion.recording.clone());
+                        replay.world.network = champion.network.clone();
+                        (replay, network.clone())
+                    });
+                }
+            }
+            match &champion_replay {
+                Some((replay, network)) => {
+                    let snapshot = WorldSnapshot::new(&replay.world, network.clone());
+                    draw_world(&snapshot, show_sensors);
+                    if show_network {
+                        network_panel::draw_network_panel(&snapshot);
+                    }
+                }
+                None => {
+                    draw_text("Waiting for the first champion", 0.0, 80.0, 40.0, BLUE);
+                }
+            }
+        } else {
+            draw_world(&gamestate.world, show_sensors);
+            if let Some(cause) = gamestate.world.death_cause {
+                draw_text(&format!("Died: {}", cause.name()), 0.0, 80.0, 40.0, BLUE);
+            }
+            if show_network {
+                network_panel::draw_network_panel(&gamestate.world);
+            }
+        }
+        if show_charts {
+            charts::draw_charts(&history);
+        }
+        draw_text(
+            &format!("Generation {}", gamestate.generation),
+            0.0,
+            20.0,
+            40.0,
+            BLUE,
+        );
+        draw_text(
+            &format!("Best apples: {}", gamestate.top_apples),
+            0.0,
+            50.0,
+            40.0,
+            BLUE,
+        );
+        draw_status(&gamestate, trainer_finished);
+        next_frame().await
+    }
+}
+// Controls and what they are set to, along the bottom of the window
+fn draw_status(gamestate: &GameState, trainer_finished: bool) {
+    let mut status = format!(
+        "{}: world {}, delay {} ms",
+        gamestate.display_mode.name(),
+        gamestate.followed_world,
+        gamestate.tick_delay.as_millis()
+    );
+    if let Some(rate) = gamestate.mutation_rate {
+        status += &format!(", mutation rate {:.4}", rate);
+    }
+    if gamestate.paused {
+        status += ", paused";
+    }
+    if trainer_finished {
+        status += ", training has finished";
+    }
+    draw_text(&status, 5.0, SCREEN_SIZE - 28.0, 20.0, WHITE);
+    draw_text(
+        "Space pause, Up/Down speed, K checkpoint, [/] world, M mode, +/- mutation, G grid, N network, S sensors, C charts",
+        5.0,
+        SCREEN_SIZE - 8.0,
+        16.0,
+        WHITE,
+    );
+}
+fn draw_world(world: &WorldSnapshot, show_sensors: bool) {
+    draw_board(
+        &world.snake,
+        world.apple,
+        &world.obstacles,
+        Vec2::ZERO,
+        CELL_SIZE,
+        world.width,
+        world.height,
+    );
+    if show_sensors {
+        sensors::draw_sensor_rays(world);
+    }
+}
+// Board with its top left corner at the given position, the same at any cell size
+pub fn draw_board(
+    snake: &Vec<Point>,
+    apple: Point,
+    obstacles: &[Point],
+    top_left: Vec2,
+    cell_size: f32,
+    width: i32,
+    height: i32,
+) {
+    let (x, y) = (top_left.x, top_left.y);
+    // A full size cell has a one pixel gap, smaller ones keep the same proportion
+    let gap = cell_size / CELL_SIZE;
+    draw_rectangle_lines(
+        x,
+        y,
+        width as f32 * cell_size,
+        height as f32 * cell_size,
+        cell_size * 2.0,
+        RED,
+    );
+    // Obstacles are walls inside the board
+    for obstacle in obstacles {
+        draw_rectangle(
+            x + (obstacle.x as f32) * cell_size,
+            y + (obstacle.y as f32) * cell_size,
+            cell_size - gap,
+            cell_size - gap,
+            RED,
+        );
+    }
+    for snake_point in snake {
+        draw_rectangle(
+            x + (snake_point.x as f32) * cell_size,
+            y + (snake_point.y as f32) * cell_size,
+            cell_size - gap,
+            cell_size - gap,
+            YELLOW,
+        );
+    }
+    draw_rectangle(
+        x + (apple.x as f32) * cell_size,
+        y + (apple.y as f32) * cell_size,
+        cell_size - gap,
+        cell_size - gap,
+        GREEN,
+    );
+}
+// One frame for the window, sent every tick but only the latest one is drawn
+#[derive(Clone)]
+pub struct GameState {
+    pub world: WorldSnapshot,
+    pub top_apples: usize,
+    pub generation: usize,
+    // The first worlds of the population, for the grid view
+    pub thumbnails: Vec<WorldThumbnail>,
+    pub followed_world: usize,
+    pub mutation_rate: Option<f32>,
+    pub tick_delay: Duration,
+    pub display_mode: DisplayMode,
+    // Taken from the trainer, so a pause shows once the training has really stopped
+    pub paused: bool,
+}
+// Sent once per generation, so unlike frames these are never skipped while the window keeps up
+pub struct GenerationReport {
+    pub stats: GenerationStats,
+    pub generations_per_second: f32,
+    // Best world of the generation, for the champion replay
+    pub champion: World,
+}
+// Two decimals are enough to tell the sensors and activations apart
+fn format_value(value: f32) -> String {
+    return format!("{:.2}", value);
+}
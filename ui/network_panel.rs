@@ -0,0 +1,120 @@
+use macroquad::{
+    color::*,
+    shapes::{draw_circle, draw_circle_lines, draw_line, draw_rectangle},
+    text::draw_text,
+};
+
+use crate::{
+    SCREEN_SIZE,
+    game::{Direction, world::World},
+    ui::{format_value, snapshot::WorldSnapshot},
+};
+
+const PANEL_WIDTH: f32 = 500.0;
+const PANEL_TOP: f32 = 100.0;
+const PANEL_MARGIN: f32 = 10.0;
+// Room for the labels on both sides of the network
+const INPUT_LABELS_WIDTH: f32 = 160.0;
+const OUTPUT_LABELS_WIDTH: f32 = 70.0;
+const NODE_RADIUS: f32 = 6.0;
+const LABEL_FONT_SIZE: f32 = 16.0;
+
+/*
+ * Draws the network of the world over the right part of the board.
+ * Edge colour is the sign of the weight (green positive, red negative), thickness its size.
+ * Nodes are filled by how strongly they are activated for the current inputs.
+ */
+pub fn draw_network_panel(world: &WorldSnapshot) {
+    let left = SCREEN_SIZE - PANEL_WIDTH;
+    let height = SCREEN_SIZE - PANEL_TOP - PANEL_MARGIN;
+    draw_rectangle(
+        left,
+        PANEL_TOP,
+        PANEL_WIDTH - PANEL_MARGIN,
+        height,
+        Color::new(0.0, 0.0, 0.0, 0.75),
+    );
+
+    let activations = &world.activations;
+    let columns = activations.len();
+    if columns < 2 {
+        return;
+    }
+    let first_column = left + INPUT_LABELS_WIDTH;
+    let column_step = (PANEL_WIDTH - PANEL_MARGIN - INPUT_LABELS_WIDTH - OUTPUT_LABELS_WIDTH)
+        / (columns - 1) as f32;
+    let node_position = |column: usize, node: usize| {
+        let nodes = activations[column].len().max(1);
+        return (
+            first_column + column as f32 * column_step,
+            PANEL_TOP + height * (node as f32 + 0.5) / nodes as f32,
+        );
+    };
+
+    for (layer_id, layer) in world.network.layers.iter().enumerate() {
+        let inputs_count = activations[layer_id].len();
+        for (neuron_id, neuron) in layer.neurons.iter().enumerate() {
+            let (x2, y2) = node_position(layer_id + 1, neuron_id);
+            // Weights past the actual inputs get nothing to multiply, so they aren't drawn
+            for (input_id, weight) in neuron.weights.iter().take(inputs_count).enumerate() {
+                let (x1, y1) = node_position(layer_id, input_id);
+                let strength = weight.abs().min(1.0);
+                let color = if *weight >= 0.0 {
+                    Color::new(0.2, 0.9, 0.2, 0.1 + 0.5 * strength)
+                } else {
+                    Color::new(0.9, 0.2, 0.2, 0.1 + 0.5 * strength)
+                };
+                draw_line(x1, y1, x2, y2, 0.5 + 2.0 * strength, color);
+            }
+        }
+    }
+
+    let output = &activations[columns - 1];
+    let chosen = (0..output.len()).max_by(|i1, i2| output[*i1].total_cmp(&output[*i2]));
+    for (column, column_activations) in activations.iter().enumerate() {
+        for (node, value) in column_activations.iter().enumerate() {
+            let (x, y) = node_position(column, node);
+            let level = activation_level(*value);
+            let fill = if *value < 0.0 {
+                Color::new(0.3, 0.5, 1.0, 0.2 + 0.8 * level)
+            } else {
+                Color::new(1.0, 1.0, 0.6, 0.2 + 0.8 * level)
+            };
+            draw_circle(x, y, NODE_RADIUS, fill);
+            if column == 0 {
+                let label = World::INPUT_LABELS.get(node).copied().unwrap_or("Input");
+                draw_text(
+                    &format!("{} {}", label, format_value(*value)),
+                    left + PANEL_MARGIN,
+                    y + LABEL_FONT_SIZE / 4.0,
+                    LABEL_FONT_SIZE,
+                    WHITE,
+                );
+            }
+            if column == columns - 1 {
+                if chosen == Some(node) {
+                    draw_circle_lines(x, y, NODE_RADIUS + 3.0, 2.0, YELLOW);
+                }
+                let label = if node < 4 {
+                    format!("{:?}", Direction::from_index(node))
+                } else {
+                    format!("Output {}", node)
+                };
+                draw_text(
+                    &format!("{} {}", label, format_value(*value)),
+                    x + NODE_RADIUS * 2.0,
+                    y + LABEL_FONT_SIZE / 4.0,
+                    LABEL_FONT_SIZE,
+                    WHITE,
+                );
+            }
+        }
+    }
+}
+// 0 for nothing, close to 1 for large values, 1 for infinities
+fn activation_level(value: f32) -> f32 {
+    if !value.is_finite() {
+        return 1.0;
+    }
+    return value.abs() / (1.0 + value.abs());
+}
@@ -0,0 +1,121 @@
+use macroquad::{
+    color::*,
+    shapes::{draw_line, draw_rectangle, draw_rectangle_lines},
+    text::draw_text,
+};
+
+use crate::{
+    CELL_SIZE,
+    game::{Point, world::World},
+    ui::{format_value, snapshot::WorldSnapshot},
+};
+
+const SENSOR_FONT_SIZE: f32 = 18.0;
+
+// Ray directions in the order get_inputs reads them: right, up, left, down, then the diagonals
+const RAYS: [Point; 8] = [
+    Point { x: 1, y: 0 },
+    Point { x: 0, y: -1 },
+    Point { x: -1, y: 0 },
+    Point { x: 0, y: 1 },
+    Point { x: 1, y: -1 },
+    Point { x: -1, y: -1 },
+    Point { x: -1, y: 1 },
+    Point { x: 1, y: 1 },
+];
+// Where every sensor kind starts in the inputs
+const WALL_INPUTS: usize = 0;
+const BODY_INPUTS: usize = 8;
+const APPLE_INPUTS: usize = 16;
+// Inputs that aren't rays: apple quadrant, apple distance and the direction
+const OTHER_INPUTS: usize = 20;
+
+/*
+ * Draws the eight rays the sensors look along, marking the wall cell, the first body part and
+ * the apple each ray really hits. Next to every ray are the values the network gets for it,
+ * so a sensor that sees something else than the ray shows up as a wrong number.
+ */
+pub fn draw_sensor_rays(world: &WorldSnapshot) {
+    let inputs = &world.inputs;
+    let head = world.snake[0];
+    let center = |point: Point| {
+        (
+            (point.x as f32 + 0.5) * CELL_SIZE,
+            (point.y as f32 + 0.5) * CELL_SIZE,
+        )
+    };
+    for (ray_id, ray) in RAYS.iter().enumerate() {
+        let mut point = head;
+        let mut body_hit = None;
+        let mut apple_hit = None;
+        loop {
+            point = point + *ray;
+            if point.x <= 0
+                || point.y <= 0
+                || point.x >= world.width - 1
+                || point.y >= world.height - 1
+            {
+                break;
+            }
+            if body_hit.is_none() && world.snake[1..].contains(&point) {
+                body_hit = Some(point);
+            }
+            if point == world.apple {
+                apple_hit = Some(point);
+            }
+        }
+        let (x1, y1) = center(head);
+        let (x2, y2) = center(point);
+        draw_line(x1, y1, x2, y2, 2.0, Color::new(1.0, 1.0, 1.0, 0.6));
+        mark_cell(point, ORANGE);
+        if let Some(body) = body_hit {
+            mark_cell(body, MAGENTA);
+        }
+        if let Some(apple) = apple_hit {
+            mark_cell(apple, LIME);
+        }
+
+        let mut label = format!(
+            "W {} B {}",
+            format_value(inputs[WALL_INPUTS + ray_id]),
+            format_value(inputs[BODY_INPUTS + ray_id])
+        );
+        // Only the straight rays have an apple distance sensor
+        if ray_id < 4 {
+            label += &format!(" A {}", format_value(inputs[APPLE_INPUTS + ray_id]));
+        }
+        // Pulled back from the wall a bit, so the text stays on the board
+        let (label_x, label_y) = center(point - *ray - *ray);
+        draw_text(&label, label_x, label_y, SENSOR_FONT_SIZE, WHITE);
+    }
+
+    let other_inputs: Vec<String> = (OTHER_INPUTS..inputs.len())
+        .map(|input_id| {
+            format!(
+                "{}: {}",
+                World::INPUT_LABELS
+                    .get(input_id)
+                    .copied()
+                    .unwrap_or("Input"),
+                format_value(inputs[input_id])
+            )
+        })
+        .collect();
+    for (line_id, line) in other_inputs.iter().enumerate() {
+        let y = world.height as f32 * CELL_SIZE
+            - CELL_SIZE * 1.5
+            - (other_inputs.len() - 1 - line_id) as f32 * SENSOR_FONT_SIZE;
+        draw_text(line, CELL_SIZE * 1.5, y, SENSOR_FONT_SIZE, WHITE);
+    }
+}
+fn mark_cell(point: Point, color: Color) {
+    let (x, y) = (point.x as f32 * CELL_SIZE, point.y as f32 * CELL_SIZE);
+    draw_rectangle(
+        x,
+        y,
+        CELL_SIZE,
+        CELL_SIZE,
+        Color::new(color.r, color.g, color.b, 0.4),
+    );
+    draw_rectangle_lines(x, y, CELL_SIZE, CELL_SIZE, 2.0, color);
+}
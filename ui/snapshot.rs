@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use crate::{
+    game::{Point, world::DeathCause, world::World},
+    perceptron::network::Network,
+};
+
+// What the window needs to draw a world and its network, without cloning the whole world
+#[derive(Clone)]
+pub struct WorldSnapshot {
+    pub snake: Vec<Point>,
+    pub apple: Point,
+    pub obstacles: Vec<Point>,
+    pub width: i32,
+    pub height: i32,
+    pub death_cause: Option<DeathCause>,
+    // Inputs the network gets on this tick and what every layer makes of them
+    pub inputs: Vec<f32>,
+    pub activations: Vec<Vec<f32>>,
+    // Shared, so a snapshot per tick doesn't copy the weights every time
+    pub network: Arc<Network>,
+}
+impl WorldSnapshot {
+    // The network has to be the world's own, it is only passed in to be shared
+    pub fn new(world: &World, network: Arc<Network>) -> Self {
+        let inputs = world.get_inputs();
+        let activations = network.forward_trace(inputs.clone()).activations;
+        return Self {
+            snake: world.snake.points.clone(),
+            apple: world.apple.position,
+            obstacles: world.obstacles.clone(),
+            width: world.width,
+            height: world.height,
+            death_cause: world.death_cause,
+            inputs,
+            activations,
+            network,
+        };
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+use tokio::sync::{mpsc::Receiver, watch};
+
+use crate::{
+    render::raster::{Canvas, palette_rgb, render_board},
+    ui::{GameState, GenerationReport, charts::ChartHistory},
+};
+
+// Redrawing faster only makes the terminal flicker
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+// Generations the fitness sparkline shows, one character each
+const SPARKLINE_GENERATIONS: usize = 60;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ASCII_SPARKS: [char; 8] = ['_', '.', ',', '-', '~', '=', '+', '#'];
+// Two characters per cell keep the board roughly square, indexed like the raster palette
+const ASCII_CELLS: [&str; 4] = ["  ", "##", "[]", "()"];
+
+/*
+ * Draws the followed world and a dashboard into the terminal instead of a window, for
+ * sessions without a display. The trainer keeps printing its generation summaries, the next
+ * redraw paints over them.
+ */
+pub fn open_terminal(
+    frames: watch::Receiver<Option<GameState>>,
+    reports: Receiver<GenerationReport>,
+    ascii: bool,
+) {
+    thread::spawn(move || {
+        if let Err(err) = draw_terminal(frames, reports, ascii) {
+            eprintln!("Terminal renderer stopped: {}", err);
+        }
+    });
+}
+
+fn draw_terminal(
+    mut frames: watch::Receiver<Option<GameState>>,
+    mut reports: Receiver<GenerationReport>,
+    ascii: bool,
+) -> io::Result<()> {
+    let mut history = ChartHistory::default();
+    let mut stdout = io::stdout();
+    // Clear once, afterwards every frame only overwrites the previous one
+    write!(stdout, "\x1b[2J")?;
+    loop {
+        let trainer_finished = frames.has_changed().is_err();
+        while let Ok(report) = reports.try_recv() {
+            history.push(report.stats, report.generations_per_second);
+        }
+        let mut screen = String::from("\x1b[H");
+        match frames.borrow_and_update().as_ref() {
+            Some(gamestate) => {
+                let world = &gamestate.world;
+                let board = render_board(
+                    &world.snake,
+                    world.apple,
+                    &world.obstacles,
+                    world.width,
+                    world.height,
+                    1.0,
+                );
+                if ascii {
+                    screen += &ascii_board(&board);
+                } else {
+                    screen += &unicode_board(&board);
+                }
+                screen += &dashboard(gamestate, &history, ascii);
+            }
+            None => screen += "Waiting for the trainer\n",
+        }
+        if trainer_finished {
+            screen += "Training has finished\n";
+        }
+        // Whatever the trainer printed below the last frame goes away too
+        screen += "\x1b[J";
+        stdout.write_all(screen.as_bytes())?;
+        stdout.flush()?;
+        if trainer_finished {
+            return Ok(());
+        }
+        thread::sleep(REFRESH_INTERVAL);
+    }
+}
+
+// Upper half blocks coloured like the window, two board rows per terminal line
+fn unicode_board(board: &Canvas) -> String {
+    let mut lines = String::new();
+    for row in (0..board.height).step_by(2) {
+        for column in 0..board.width {
+            let [r, g, b] = palette_rgb(board.pixels[row * board.width + column] as usize);
+            lines += &format!("\x1b[38;2;{};{};{}m", r, g, b);
+            // An odd last row has nothing under it, the terminal background stays
+            if row + 1 < board.height {
+                let [r, g, b] =
+                    palette_rgb(board.pixels[(row + 1) * board.width + column] as usize);
+                lines += &format!("\x1b[48;2;{};{};{}m", r, g, b);
+            }
+            lines.push('▀');
+        }
+        lines += "\x1b[0m\x1b[K\n";
+    }
+    return lines;
+}
+fn ascii_board(board: &Canvas) -> String {
+    let mut lines = String::new();
+    for row in board.pixels.chunks(board.width.max(1)) {
+        for pixel in row {
+            lines += ASCII_CELLS[*pixel as usize];
+        }
+        lines += "\x1b[K\n";
+    }
+    return lines;
+}
+
+fn dashboard(gamestate: &GameState, history: &ChartHistory, ascii: bool) -> String {
+    let mut lines = vec![format!(
+        "Generation {}, best apples {}, {} {}",
+        gamestate.generation,
+        gamestate.top_apples,
+        gamestate.display_mode.name(),
+        gamestate.followed_world
+    )];
+    let start = history.stats.len().saturating_sub(SPARKLINE_GENERATIONS);
+    let best_fitness: Vec<f32> = history.stats[start..]
+        .iter()
+        .map(|stats| stats.best_fitness as f32)
+        .collect();
+    lines.push(format!(
+        "Best fitness {} {}",
+        sparkline(&best_fitness, if ascii { &ASCII_SPARKS } else { &SPARKS }),
+        best_fitness.last().copied().unwrap_or(0.0)
+    ));
+    if let (Some(stats), Some(generations_per_second)) =
+        (history.stats.last(), history.generations_per_second.last())
+    {
+        // Every world of a finished generation has died exactly once
+        let episodes = stats.deaths.iter().sum::<usize>() as f32;
+        lines.push(format!(
+            "Throughput {:.2} generations/s, {:.0} episodes/s",
+            generations_per_second,
+            episodes * generations_per_second
+        ));
+        lines.push(stats.summary());
+    }
+    return lines
+        .iter()
+        .map(|line| format!("{}\x1b[K\n", line))
+        .collect();
+}
+// Values scaled between zero and the largest one
+fn sparkline(values: &[f32], sparks: &[char; 8]) -> String {
+    let max = values
+        .iter()
+        .cloned()
+        .filter(|value| value.is_finite())
+        .fold(0.0, f32::max);
+    return values
+        .iter()
+        .map(|value| {
+            let level = if max > 0.0 { value / max } else { 0.0 };
+            sparks[((level.clamp(0.0, 1.0) * (sparks.len() - 1) as f32).round()) as usize]
+        })
+        .collect();
+}